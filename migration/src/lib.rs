@@ -4,6 +4,23 @@ mod m20260218_095047_create_users_table;
 mod m20260218_095048_create_user_tokens_table;
 mod m20260218_095049_create_trackers_table;
 mod m20260219_000000_create_pings_table;
+mod m20261018_000000_add_secret_to_trackers_table;
+mod m20261018_000100_add_telemetry_to_pings_table;
+mod m20261018_000200_add_recorded_at_to_pings_table;
+mod m20261018_000300_add_imei_to_trackers_table;
+mod m20261018_000400_create_import_jobs_table;
+mod m20261018_000500_add_last_ping_to_trackers_table;
+mod m20261018_000600_add_odometer_to_trackers_table;
+mod m20261018_000700_create_geofences_table;
+mod m20261018_000800_create_geofence_trackers_table;
+mod m20261018_000900_create_geofence_events_table;
+mod m20261018_001000_create_webhooks_table;
+mod m20261018_001100_create_webhook_deliveries_table;
+mod m20261018_001200_add_expected_interval_to_trackers_table;
+mod m20261018_001300_create_alerts_table;
+mod m20261018_001400_create_rules_table;
+mod m20261018_001500_add_rule_to_alerts_table;
+mod m20261018_001600_create_share_links_table;
 
 pub struct Migrator;
 
@@ -15,6 +32,23 @@ impl MigratorTrait for Migrator {
             Box::new(m20260218_095048_create_user_tokens_table::Migration),
             Box::new(m20260218_095049_create_trackers_table::Migration),
             Box::new(m20260219_000000_create_pings_table::Migration),
+            Box::new(m20261018_000000_add_secret_to_trackers_table::Migration),
+            Box::new(m20261018_000100_add_telemetry_to_pings_table::Migration),
+            Box::new(m20261018_000200_add_recorded_at_to_pings_table::Migration),
+            Box::new(m20261018_000300_add_imei_to_trackers_table::Migration),
+            Box::new(m20261018_000400_create_import_jobs_table::Migration),
+            Box::new(m20261018_000500_add_last_ping_to_trackers_table::Migration),
+            Box::new(m20261018_000600_add_odometer_to_trackers_table::Migration),
+            Box::new(m20261018_000700_create_geofences_table::Migration),
+            Box::new(m20261018_000800_create_geofence_trackers_table::Migration),
+            Box::new(m20261018_000900_create_geofence_events_table::Migration),
+            Box::new(m20261018_001000_create_webhooks_table::Migration),
+            Box::new(m20261018_001100_create_webhook_deliveries_table::Migration),
+            Box::new(m20261018_001200_add_expected_interval_to_trackers_table::Migration),
+            Box::new(m20261018_001300_create_alerts_table::Migration),
+            Box::new(m20261018_001400_create_rules_table::Migration),
+            Box::new(m20261018_001500_add_rule_to_alerts_table::Migration),
+            Box::new(m20261018_001600_create_share_links_table::Migration),
         ]
     }
 }
@@ -0,0 +1,50 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Pings::Table)
+                    .add_column(double_null(Pings::Altitude))
+                    .add_column(double_null(Pings::Speed))
+                    .add_column(double_null(Pings::Heading))
+                    .add_column(double_null(Pings::Accuracy))
+                    .add_column(tiny_unsigned_null(Pings::Satellites))
+                    .add_column(double_null(Pings::Battery))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Pings::Table)
+                    .drop_column(Pings::Altitude)
+                    .drop_column(Pings::Speed)
+                    .drop_column(Pings::Heading)
+                    .drop_column(Pings::Accuracy)
+                    .drop_column(Pings::Satellites)
+                    .drop_column(Pings::Battery)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Pings {
+    Table,
+    Altitude,
+    Speed,
+    Heading,
+    Accuracy,
+    Satellites,
+    Battery,
+}
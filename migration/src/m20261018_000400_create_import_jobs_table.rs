@@ -0,0 +1,107 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ImportJobs::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ImportJobs::Id).big_unsigned())
+                    .col(big_unsigned(ImportJobs::UserId).not_null())
+                    .col(big_unsigned(ImportJobs::TrackerId).not_null())
+                    .col(string(ImportJobs::Filename))
+                    .col(
+                        enumeration(
+                            ImportJobs::Status,
+                            Alias::new("status"),
+                            [
+                                Alias::new("pending"),
+                                Alias::new("running"),
+                                Alias::new("completed"),
+                                Alias::new("failed"),
+                            ],
+                        )
+                        .default("pending"),
+                    )
+                    .col(big_unsigned(ImportJobs::TotalBytes).default(0))
+                    .col(big_unsigned(ImportJobs::ProcessedBytes).default(0))
+                    .col(big_unsigned(ImportJobs::CreatedCount).default(0))
+                    .col(big_unsigned(ImportJobs::SkippedCount).default(0))
+                    .col(text_null(ImportJobs::Error))
+                    .col(
+                        timestamp(ImportJobs::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp(ImportJobs::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_status")
+                            .table(ImportJobs::Table)
+                            .col(ImportJobs::Status),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(ImportJobs::Table)
+                            .from_col(ImportJobs::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(ImportJobs::Table)
+                            .from_col(ImportJobs::TrackerId)
+                            .to_tbl(Trackers::Table)
+                            .to_col(Trackers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ImportJobs::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ImportJobs {
+    Table,
+    Id,
+    UserId,
+    TrackerId,
+    Filename,
+    Status,
+    TotalBytes,
+    ProcessedBytes,
+    CreatedCount,
+    SkippedCount,
+    Error,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Trackers {
+    Table,
+    Id,
+}
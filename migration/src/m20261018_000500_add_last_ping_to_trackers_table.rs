@@ -0,0 +1,80 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Trackers::Table)
+                    .add_column(big_unsigned_null(Trackers::LastPingId))
+                    .add_column(timestamp_null(Trackers::LastPingAt))
+                    .add_column(double_null(Trackers::LastLat))
+                    .add_column(double_null(Trackers::LastLon))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE trackers t
+                JOIN pings p ON p.id = (
+                    SELECT id FROM pings
+                    WHERE tracker_id = t.id
+                    ORDER BY recorded_at DESC, id DESC
+                    LIMIT 1
+                )
+                SET t.last_ping_id = p.id,
+                    t.last_ping_at = p.recorded_at,
+                    t.last_lat = p.lat,
+                    t.last_lon = p.lon",
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_last_ping_at")
+                    .table(Trackers::Table)
+                    .col(Trackers::LastPingAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_last_ping_at")
+                    .table(Trackers::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Trackers::Table)
+                    .drop_column(Trackers::LastPingId)
+                    .drop_column(Trackers::LastPingAt)
+                    .drop_column(Trackers::LastLat)
+                    .drop_column(Trackers::LastLon)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Trackers {
+    Table,
+    LastPingId,
+    LastPingAt,
+    LastLat,
+    LastLon,
+}
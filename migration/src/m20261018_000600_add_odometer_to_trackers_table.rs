@@ -0,0 +1,60 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Trackers::Table)
+                    .add_column(double(Trackers::OdometerM).default(0))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE trackers t
+                JOIN (
+                    SELECT tracker_id, SUM(2 * 6371008.8 * ASIN(LEAST(1, SQRT(
+                        POW(SIN(RADIANS(lat - prev_lat) / 2), 2) +
+                        COS(RADIANS(prev_lat)) * COS(RADIANS(lat)) *
+                        POW(SIN(RADIANS(lon - prev_lon) / 2), 2)
+                    )))) AS distance
+                    FROM (
+                        SELECT tracker_id, lat, lon,
+                            LAG(lat) OVER w AS prev_lat,
+                            LAG(lon) OVER w AS prev_lon
+                        FROM pings
+                        WINDOW w AS (PARTITION BY tracker_id ORDER BY recorded_at, id)
+                    ) legs
+                    GROUP BY tracker_id
+                ) o ON o.tracker_id = t.id
+                SET t.odometer_m = COALESCE(o.distance, 0)",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Trackers::Table)
+                    .drop_column(Trackers::OdometerM)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Trackers {
+    Table,
+    OdometerM,
+}
@@ -0,0 +1,81 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Geofences::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Geofences::Id).big_unsigned())
+                    .col(big_unsigned(Geofences::UserId).not_null())
+                    .col(string(Geofences::Name))
+                    .col(enumeration(
+                        Geofences::Shape,
+                        Alias::new("shape"),
+                        [Alias::new("circle"), Alias::new("polygon")],
+                    ))
+                    .col(double_null(Geofences::Lat))
+                    .col(double_null(Geofences::Lon))
+                    .col(double_null(Geofences::RadiusM))
+                    .col(json_null(Geofences::Points))
+                    .col(
+                        timestamp(Geofences::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp(Geofences::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_name")
+                            .table(Geofences::Table)
+                            .col(Geofences::Name),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(Geofences::Table)
+                            .from_col(Geofences::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Geofences::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Geofences {
+    Table,
+    Id,
+    UserId,
+    Name,
+    Shape,
+    Lat,
+    Lon,
+    RadiusM,
+    Points,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
@@ -0,0 +1,72 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GeofenceTrackers::Table)
+                    .if_not_exists()
+                    .col(big_unsigned(GeofenceTrackers::GeofenceId).not_null())
+                    .col(big_unsigned(GeofenceTrackers::TrackerId).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(GeofenceTrackers::GeofenceId)
+                            .col(GeofenceTrackers::TrackerId),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_tracker_id")
+                            .table(GeofenceTrackers::Table)
+                            .col(GeofenceTrackers::TrackerId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(GeofenceTrackers::Table)
+                            .from_col(GeofenceTrackers::GeofenceId)
+                            .to_tbl(Geofences::Table)
+                            .to_col(Geofences::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(GeofenceTrackers::Table)
+                            .from_col(GeofenceTrackers::TrackerId)
+                            .to_tbl(Trackers::Table)
+                            .to_col(Trackers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GeofenceTrackers::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GeofenceTrackers {
+    Table,
+    GeofenceId,
+    TrackerId,
+}
+
+#[derive(DeriveIden)]
+enum Geofences {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Trackers {
+    Table,
+    Id,
+}
@@ -0,0 +1,107 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GeofenceEvents::Table)
+                    .if_not_exists()
+                    .col(pk_auto(GeofenceEvents::Id).big_unsigned())
+                    .col(big_unsigned(GeofenceEvents::GeofenceId).not_null())
+                    .col(big_unsigned(GeofenceEvents::TrackerId).not_null())
+                    .col(big_unsigned(GeofenceEvents::PingId).not_null())
+                    .col(enumeration(
+                        GeofenceEvents::Transition,
+                        Alias::new("transition"),
+                        [Alias::new("enter"), Alias::new("exit")],
+                    ))
+                    .col(timestamp(GeofenceEvents::RecordedAt).not_null())
+                    .col(
+                        timestamp(GeofenceEvents::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_geofence_id_recorded_at")
+                            .table(GeofenceEvents::Table)
+                            .col(GeofenceEvents::GeofenceId)
+                            .col(GeofenceEvents::RecordedAt),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_tracker_id_recorded_at")
+                            .table(GeofenceEvents::Table)
+                            .col(GeofenceEvents::TrackerId)
+                            .col(GeofenceEvents::RecordedAt),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(GeofenceEvents::Table)
+                            .from_col(GeofenceEvents::GeofenceId)
+                            .to_tbl(Geofences::Table)
+                            .to_col(Geofences::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(GeofenceEvents::Table)
+                            .from_col(GeofenceEvents::TrackerId)
+                            .to_tbl(Trackers::Table)
+                            .to_col(Trackers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(GeofenceEvents::Table)
+                            .from_col(GeofenceEvents::PingId)
+                            .to_tbl(Pings::Table)
+                            .to_col(Pings::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GeofenceEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GeofenceEvents {
+    Table,
+    Id,
+    GeofenceId,
+    TrackerId,
+    PingId,
+    Transition,
+    RecordedAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Geofences {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Trackers {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Pings {
+    Table,
+    Id,
+}
@@ -0,0 +1,71 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Webhooks::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Webhooks::Id).big_unsigned())
+                    .col(big_unsigned(Webhooks::UserId).not_null())
+                    .col(string_len(Webhooks::Url, 2048))
+                    .col(string(Webhooks::Secret))
+                    .col(json(Webhooks::Events))
+                    .col(json_null(Webhooks::TrackerIds))
+                    .col(boolean(Webhooks::Enabled).default(true))
+                    .col(unsigned(Webhooks::FailureCount).default(0))
+                    .col(
+                        timestamp(Webhooks::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp(Webhooks::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(Webhooks::Table)
+                            .from_col(Webhooks::UserId)
+                            .to_tbl(Users::Table)
+                            .to_col(Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Webhooks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Webhooks {
+    Table,
+    Id,
+    UserId,
+    Url,
+    Secret,
+    Events,
+    TrackerIds,
+    Enabled,
+    FailureCount,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
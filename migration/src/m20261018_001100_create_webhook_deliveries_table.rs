@@ -0,0 +1,93 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDeliveries::Table)
+                    .if_not_exists()
+                    .col(pk_auto(WebhookDeliveries::Id).big_unsigned())
+                    .col(big_unsigned(WebhookDeliveries::WebhookId).not_null())
+                    .col(string(WebhookDeliveries::Event))
+                    .col(text(WebhookDeliveries::Payload))
+                    .col(
+                        enumeration(
+                            WebhookDeliveries::Status,
+                            Alias::new("delivery_status"),
+                            [
+                                Alias::new("pending"),
+                                Alias::new("succeeded"),
+                                Alias::new("failed"),
+                            ],
+                        )
+                        .default("pending"),
+                    )
+                    .col(unsigned(WebhookDeliveries::Attempts).default(0))
+                    .col(small_unsigned_null(WebhookDeliveries::ResponseStatus))
+                    .col(text_null(WebhookDeliveries::Error))
+                    .col(timestamp_null(WebhookDeliveries::NextAttemptAt))
+                    .col(timestamp_null(WebhookDeliveries::DeliveredAt))
+                    .col(
+                        timestamp(WebhookDeliveries::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp(WebhookDeliveries::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_status_next_attempt_at")
+                            .table(WebhookDeliveries::Table)
+                            .col(WebhookDeliveries::Status)
+                            .col(WebhookDeliveries::NextAttemptAt),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(WebhookDeliveries::Table)
+                            .from_col(WebhookDeliveries::WebhookId)
+                            .to_tbl(Webhooks::Table)
+                            .to_col(Webhooks::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDeliveries::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WebhookDeliveries {
+    Table,
+    Id,
+    WebhookId,
+    Event,
+    Payload,
+    Status,
+    Attempts,
+    ResponseStatus,
+    Error,
+    NextAttemptAt,
+    DeliveredAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Webhooks {
+    Table,
+    Id,
+}
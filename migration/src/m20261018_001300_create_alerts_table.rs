@@ -0,0 +1,81 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Alerts::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Alerts::Id).big_unsigned())
+                    .col(big_unsigned(Alerts::TrackerId).not_null())
+                    .col(string(Alerts::Kind))
+                    .col(string(Alerts::Message))
+                    .col(timestamp(Alerts::TriggeredAt).not_null())
+                    .col(timestamp_null(Alerts::ResolvedAt))
+                    .col(
+                        timestamp(Alerts::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp(Alerts::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_tracker_id_triggered_at")
+                            .table(Alerts::Table)
+                            .col(Alerts::TrackerId)
+                            .col(Alerts::TriggeredAt),
+                    )
+                    .index(
+                        Index::create()
+                            .name("idx_kind_resolved_at")
+                            .table(Alerts::Table)
+                            .col(Alerts::Kind)
+                            .col(Alerts::ResolvedAt),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(Alerts::Table)
+                            .from_col(Alerts::TrackerId)
+                            .to_tbl(Trackers::Table)
+                            .to_col(Trackers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Alerts::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Alerts {
+    Table,
+    Id,
+    TrackerId,
+    Kind,
+    Message,
+    TriggeredAt,
+    ResolvedAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Trackers {
+    Table,
+    Id,
+}
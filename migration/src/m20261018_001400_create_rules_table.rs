@@ -0,0 +1,86 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Rules::Table)
+                    .if_not_exists()
+                    .col(pk_auto(Rules::Id).big_unsigned())
+                    .col(big_unsigned(Rules::TrackerId).not_null())
+                    .col(enumeration(
+                        Rules::Kind,
+                        Alias::new("rule_kind"),
+                        [
+                            Alias::new("speed"),
+                            Alias::new("battery"),
+                            Alias::new("accuracy"),
+                            Alias::new("parked"),
+                        ],
+                    ))
+                    .col(double(Rules::Threshold))
+                    .col(unsigned(Rules::DurationS).default(0))
+                    .col(double_null(Rules::Lat))
+                    .col(double_null(Rules::Lon))
+                    .col(boolean(Rules::Notify).default(true))
+                    .col(boolean(Rules::Enabled).default(true))
+                    .col(timestamp_null(Rules::BreachStartedAt))
+                    .col(timestamp_null(Rules::TriggeredAt))
+                    .col(
+                        timestamp(Rules::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        timestamp(Rules::UpdatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(Rules::Table)
+                            .from_col(Rules::TrackerId)
+                            .to_tbl(Trackers::Table)
+                            .to_col(Trackers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Rules::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Rules {
+    Table,
+    Id,
+    TrackerId,
+    Kind,
+    Threshold,
+    DurationS,
+    Lat,
+    Lon,
+    Notify,
+    Enabled,
+    BreachStartedAt,
+    TriggeredAt,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Trackers {
+    Table,
+    Id,
+}
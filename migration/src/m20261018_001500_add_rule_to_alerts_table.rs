@@ -0,0 +1,59 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alerts::Table)
+                    .add_column(big_unsigned_null(Alerts::RuleId))
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_alerts_rule_id")
+                            .from_tbl(Alerts::Table)
+                            .from_col(Alerts::RuleId)
+                            .to_tbl(Rules::Table)
+                            .to_col(Rules::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alerts::Table)
+                    .drop_foreign_key(Alias::new("fk_alerts_rule_id"))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Alerts::Table)
+                    .drop_column(Alerts::RuleId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Alerts {
+    Table,
+    RuleId,
+}
+
+#[derive(DeriveIden)]
+enum Rules {
+    Table,
+    Id,
+}
@@ -0,0 +1,63 @@
+use sea_orm_migration::{prelude::*, schema::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ShareLinks::Table)
+                    .if_not_exists()
+                    .col(pk_auto(ShareLinks::Id).big_unsigned())
+                    .col(big_unsigned(ShareLinks::TrackerId).not_null())
+                    .col(string(ShareLinks::Token).unique_key())
+                    .col(timestamp_null(ShareLinks::ExpiresAt))
+                    .col(timestamp_null(ShareLinks::WindowFrom))
+                    .col(timestamp_null(ShareLinks::WindowTo))
+                    .col(tiny_unsigned_null(ShareLinks::Precision))
+                    .col(
+                        timestamp(ShareLinks::CreatedAt)
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from_tbl(ShareLinks::Table)
+                            .from_col(ShareLinks::TrackerId)
+                            .to_tbl(Trackers::Table)
+                            .to_col(Trackers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ShareLinks::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ShareLinks {
+    Table,
+    Id,
+    TrackerId,
+    Token,
+    ExpiresAt,
+    WindowFrom,
+    WindowTo,
+    Precision,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Trackers {
+    Table,
+    Id,
+}
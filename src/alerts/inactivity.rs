@@ -0,0 +1,159 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter,
+    TransactionTrait,
+};
+use std::collections::HashMap;
+use tracing::error;
+
+use crate::{
+    AppState, Result, alerts,
+    entity::{
+        alerts as alert,
+        prelude::{Alerts, Trackers, Users},
+        trackers,
+    },
+    events::{self, Event},
+    mail::tracker::{send_inactive, send_recovered},
+    webhooks,
+};
+
+pub const KIND: &str = "inactivity";
+
+/// Seconds between looks for trackers that went silent
+const CHECK_SECONDS: u64 = 60;
+
+/// Starts checking trackers with an expected interval for silence
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(CHECK_SECONDS));
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = check(&state).await {
+                error!("Could not check trackers for inactivity: {err}");
+            }
+        }
+    });
+}
+
+/// Raises an alert for every tracker whose last ping is older than its expected interval and
+/// resolves the alerts of those reporting again. A tracker has at most one open alert, so an
+/// outage is only reported once however long it lasts.
+async fn check(state: &AppState) -> Result<()> {
+    let now = Utc::now();
+
+    let trackers = Trackers::find()
+        .filter(trackers::Column::ExpectedIntervalS.is_not_null())
+        .all(&state.db)
+        .await?;
+
+    let mut open: HashMap<u64, alert::Model> = Alerts::find()
+        .filter(alert::Column::Kind.eq(KIND))
+        .filter(alert::Column::ResolvedAt.is_null())
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|alert| (alert.tracker_id, alert))
+        .collect();
+
+    for tracker in trackers {
+        // INFO: A tracker that never reported can't go silent
+        let (Some(interval), Some(last_ping_at)) =
+            (tracker.expected_interval_s, tracker.last_ping_at)
+        else {
+            continue;
+        };
+
+        let silent = now - last_ping_at > Duration::seconds(interval.into());
+
+        match (silent, open.remove(&tracker.id)) {
+            (true, None) => raise(state, tracker, last_ping_at).await?,
+            (false, Some(alert)) => {
+                resolve(state, alert).await?;
+                notify(state, tracker, None).await?;
+            }
+            _ => {}
+        }
+    }
+
+    // INFO: Trackers that aren't watched anymore have nothing to recover from
+    for alert in open.into_values() {
+        resolve(state, alert).await?;
+    }
+
+    Ok(())
+}
+
+async fn raise(
+    state: &AppState,
+    tracker: trackers::Model,
+    last_ping_at: DateTime<Utc>,
+) -> Result<()> {
+    let txn = state.db.begin().await?;
+
+    let alert = alert::ActiveModel {
+        tracker_id: Set(tracker.id),
+        kind: Set(KIND.into()),
+        message: Set(format!(
+            "No ping since {}",
+            last_ping_at.format("%Y-%m-%d %H:%M:%S UTC")
+        )),
+        triggered_at: Set(Utc::now()),
+
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    let event = Event::Alert {
+        user_id: tracker.user_id,
+        tracker_id: tracker.id,
+        alert,
+    };
+
+    webhooks::enqueue(&txn, std::slice::from_ref(&event)).await?;
+    txn.commit().await?;
+
+    events::publish(state, event);
+
+    notify(state, tracker, Some(last_ping_at)).await
+}
+
+async fn resolve(state: &AppState, alert: alert::Model) -> Result<()> {
+    let mut alert = alert.into_active_model();
+    alert.resolved_at = Set(Some(Utc::now()));
+    alert.updated_at = Set(Utc::now());
+    alert.save(&state.db).await?;
+
+    Ok(())
+}
+
+/// Mails the owner that the tracker went silent `since`, or recovered without it
+async fn notify(
+    state: &AppState,
+    tracker: trackers::Model,
+    since: Option<DateTime<Utc>>,
+) -> Result<()> {
+    let Some(user) = Users::find_by_id(tracker.user_id).one(&state.db).await? else {
+        return Ok(());
+    };
+
+    let link = alerts::link(state, tracker.id)?;
+    let mail = state.mail.clone();
+
+    // INFO: The SMTP transport blocks
+    tokio::task::spawn_blocking(move || {
+        let sent = match since {
+            Some(since) => send_inactive(&mail, &user, &tracker, since, link.as_str()),
+            None => send_recovered(&mail, &user, &tracker, link.as_str()),
+        };
+
+        if let Err(err) = sent {
+            error!("Could not mail inactivity of tracker {}: {err}", tracker.id);
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use url::Url;
+
+use crate::{AppState, Result};
+
+pub mod inactivity;
+pub mod rules;
+
+/// Where the owner can look at the tracker an alert is about
+pub fn link(state: &AppState, tracker_id: u64) -> Result<Url> {
+    let mut link = Url::parse(&state.spa_url)?;
+    link.path_segments_mut()
+        .map_err(|_| url::ParseError::RelativeUrlWithoutBase)?
+        .pop_if_empty()
+        .push("trackers")
+        .push(&tracker_id.to_string());
+
+    Ok(link)
+}
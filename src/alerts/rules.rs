@@ -0,0 +1,205 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveEnum, ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait,
+    DatabaseTransaction, EntityTrait, IntoActiveModel, QueryFilter, sea_query::Expr,
+};
+use tracing::error;
+
+use crate::{
+    AppState, Result, alerts,
+    entity::{
+        alerts as alert, pings,
+        prelude::{Alerts, Rules, Users},
+        rules,
+        sea_orm_active_enums::RuleKind,
+        trackers,
+    },
+    geo::{self, Point},
+    mail::tracker::send_alert,
+};
+
+const KMH_PER_MPS: f64 = 3.6;
+
+/// An alert raised by a rule and whether the rule asks for its owner to be mailed
+pub struct Triggered {
+    pub notify: bool,
+    pub alert: alert::Model,
+}
+
+/// What `ping` says about the rule: `None` when it lacks the reading the rule looks at,
+/// otherwise how the rule was broken, if it was
+fn read(rule: &rules::Model, ping: &pings::Model) -> Option<Option<String>> {
+    let threshold = rule.threshold;
+
+    match rule.kind {
+        RuleKind::Speed => ping.speed.map(|speed| {
+            let kmh = speed * KMH_PER_MPS;
+            (kmh > threshold).then(|| format!("Speed of {kmh:.0} km/h above {threshold} km/h"))
+        }),
+        RuleKind::Battery => ping.battery.map(|battery| {
+            (battery < threshold).then(|| format!("Battery at {battery:.0}% below {threshold}%"))
+        }),
+        RuleKind::Accuracy => ping.accuracy.map(|accuracy| {
+            (accuracy > threshold)
+                .then(|| format!("Accuracy of {accuracy:.0} m worse than {threshold} m"))
+        }),
+        RuleKind::Parked => {
+            let (Some(lat), Some(lon)) = (rule.lat, rule.lon) else {
+                return None;
+            };
+
+            let moved = geo::haversine(
+                Point { lat, lon },
+                Point {
+                    lat: ping.lat,
+                    lon: ping.lon,
+                },
+            );
+
+            Some((moved > threshold).then(|| format!("Moved {moved:.0} m while parked")))
+        }
+    }
+}
+
+/// Runs the enabled rules of a tracker over the pings it advanced along, in recorded order.
+/// A rule raises an alert once its condition held for `duration_s` and resolves it on the first
+/// ping that no longer breaks it. A parked tracker that moved stays moved until the rule is
+/// updated.
+pub async fn evaluate(
+    txn: &DatabaseTransaction,
+    tracker_id: u64,
+    pings: &[&pings::Model],
+) -> Result<Vec<Triggered>> {
+    if pings.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rules = Rules::find()
+        .filter(rules::Column::TrackerId.eq(tracker_id))
+        .filter(rules::Column::Enabled.eq(true))
+        .all(txn)
+        .await?;
+
+    let mut triggered = Vec::new();
+
+    for rule in rules {
+        let mut breach_started_at = rule.breach_started_at;
+        let mut triggered_at = rule.triggered_at;
+        let mut raised: Vec<alert::ActiveModel> = Vec::new();
+        // INFO: When the alert that was already open before these pings was resolved
+        let mut resolved_at = None;
+
+        for ping in pings {
+            let Some(breach) = read(&rule, ping) else {
+                continue;
+            };
+
+            let Some(mut message) = breach else {
+                breach_started_at = None;
+
+                if triggered_at.is_some() && rule.kind != RuleKind::Parked {
+                    triggered_at = None;
+
+                    match raised.last_mut() {
+                        Some(alert) => alert.resolved_at = Set(Some(ping.recorded_at)),
+                        None => resolved_at = Some(ping.recorded_at),
+                    }
+                }
+
+                continue;
+            };
+
+            let since = *breach_started_at.get_or_insert(ping.recorded_at);
+            let held = ping.recorded_at - since >= Duration::seconds(rule.duration_s.into());
+
+            if triggered_at.is_none() && held {
+                if rule.duration_s > 0 {
+                    message.push_str(&format!(" for {} s", rule.duration_s));
+                }
+
+                triggered_at = Some(ping.recorded_at);
+                raised.push(alert::ActiveModel {
+                    tracker_id: Set(tracker_id),
+                    rule_id: Set(Some(rule.id)),
+                    kind: Set(rule.kind.to_value()),
+                    message: Set(message),
+                    triggered_at: Set(ping.recorded_at),
+
+                    ..Default::default()
+                });
+            }
+        }
+
+        if let Some(resolved_at) = resolved_at {
+            resolve(txn, rule.id, resolved_at).await?;
+        }
+
+        for alert in raised {
+            triggered.push(Triggered {
+                notify: rule.notify,
+                alert: alert.insert(txn).await?,
+            });
+        }
+
+        if breach_started_at != rule.breach_started_at || triggered_at != rule.triggered_at {
+            let mut rule = rule.into_active_model();
+            rule.breach_started_at = Set(breach_started_at);
+            rule.triggered_at = Set(triggered_at);
+            rule.save(txn).await?;
+        }
+    }
+
+    Ok(triggered)
+}
+
+/// Resolves the open alert of a rule, if it has one
+pub async fn resolve<C: ConnectionTrait>(db: &C, rule_id: u64, at: DateTime<Utc>) -> Result<()> {
+    Alerts::update_many()
+        .col_expr(alert::Column::ResolvedAt, Expr::value(at))
+        .col_expr(alert::Column::UpdatedAt, Expr::value(Utc::now()))
+        .filter(alert::Column::RuleId.eq(rule_id))
+        .filter(alert::Column::ResolvedAt.is_null())
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+/// Mails the owner about the raised alerts of rules that ask for it
+pub async fn notify(
+    state: &AppState,
+    tracker: &trackers::Model,
+    triggered: Vec<Triggered>,
+) -> Result<()> {
+    let mails: Vec<_> = triggered
+        .into_iter()
+        .filter(|triggered| triggered.notify)
+        .map(|triggered| triggered.alert)
+        .collect();
+
+    if mails.is_empty() {
+        return Ok(());
+    }
+
+    let Some(user) = Users::find_by_id(tracker.user_id).one(&state.db).await? else {
+        return Ok(());
+    };
+
+    let link = alerts::link(state, tracker.id)?;
+    let mail = state.mail.clone();
+    let tracker = tracker.clone();
+
+    // INFO: The SMTP transport blocks
+    tokio::task::spawn_blocking(move || {
+        for alert in mails {
+            if let Err(err) = send_alert(&mail, &user, &tracker, &alert, link.as_str()) {
+                error!(
+                    "Could not mail alert {} of tracker {}: {err}",
+                    alert.id, tracker.id
+                );
+            }
+        }
+    });
+
+    Ok(())
+}
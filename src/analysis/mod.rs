@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+
+use crate::{
+    Result,
+    entity::{pings, prelude::Pings},
+    geo::Point,
+};
+
+pub mod stats;
+pub mod trips;
+
+/// The part of a ping the analyses look at
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub recorded_at: DateTime<Utc>,
+    pub point: Point,
+    /// Reported ground speed in meters per second
+    pub speed: Option<f64>,
+}
+
+/// Loads the samples of a tracker in the order they were recorded, both bounds inclusive
+pub async fn samples(
+    db: &DatabaseConnection,
+    tracker_id: u64,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Sample>> {
+    let rows: Vec<(DateTime<Utc>, f64, f64, Option<f64>)> = Pings::find()
+        .select_only()
+        .columns([
+            pings::Column::RecordedAt,
+            pings::Column::Lat,
+            pings::Column::Lon,
+            pings::Column::Speed,
+        ])
+        .filter(pings::Column::TrackerId.eq(tracker_id))
+        .filter(pings::Column::RecordedAt.between(from, to))
+        .order_by_asc(pings::Column::RecordedAt)
+        .order_by_asc(pings::Column::Id)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(recorded_at, lat, lon, speed)| Sample {
+            recorded_at,
+            point: Point { lat, lon },
+            speed,
+        })
+        .collect())
+}
@@ -0,0 +1,317 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::str::FromStr;
+
+use super::Sample;
+use crate::geo;
+
+/// Legs slower than this are time spent standing, not moving
+const MOVING_SPEED: f64 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bucket {
+    Day,
+    Week,
+    Month,
+}
+
+impl FromStr for Bucket {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Bucket {
+    /// The UTC midnight the bucket of `t` starts at, weeks start on Monday
+    pub fn start(&self, t: DateTime<Utc>) -> DateTime<Utc> {
+        let date = t.date_naive();
+        let date = match self {
+            Self::Day => date,
+            Self::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+            Self::Month => NaiveDate::from_ymd_opt(date.year(), date.month(), 1).unwrap_or(date),
+        };
+
+        date.and_time(Default::default()).and_utc()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Stats {
+    pub start: DateTime<Utc>,
+    pub ping_count: usize,
+    pub distance_m: f64,
+    pub moving_time_s: i64,
+    /// Meters per second, reported by the device when it does and derived from the fixes if not
+    pub max_speed: f64,
+    /// Meters per second while moving
+    pub avg_speed: f64,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    ping_count: usize,
+    distance_m: f64,
+    moving_ms: i64,
+    reported_max: Option<f64>,
+    derived_max: f64,
+}
+
+impl Accumulator {
+    fn sample(&mut self, sample: &Sample) {
+        self.ping_count += 1;
+        if let Some(speed) = sample.speed {
+            self.reported_max = Some(self.reported_max.map_or(speed, |max| max.max(speed)));
+        }
+    }
+
+    fn leg(&mut self, from: &Sample, to: &Sample) {
+        let distance = geo::haversine(from.point, to.point);
+        let ms = (to.recorded_at - from.recorded_at).num_milliseconds();
+
+        self.distance_m += distance;
+        if ms > 0 {
+            let speed = distance / (ms as f64 / 1e3);
+            self.derived_max = self.derived_max.max(speed);
+
+            if speed >= MOVING_SPEED {
+                self.moving_ms += ms;
+            }
+        }
+    }
+
+    fn finish(self, start: DateTime<Utc>) -> Stats {
+        let moving_time_s = self.moving_ms / 1000;
+
+        Stats {
+            start,
+            ping_count: self.ping_count,
+            distance_m: self.distance_m,
+            moving_time_s,
+            max_speed: self.reported_max.unwrap_or(self.derived_max),
+            avg_speed: match self.moving_ms {
+                0 => 0.0,
+                ms => self.distance_m / (ms as f64 / 1e3),
+            },
+        }
+    }
+}
+
+/// Aggregates samples ordered by time into consecutive groups keyed by `key`. A leg between
+/// two samples counts towards the group of the later one.
+fn summarize(samples: &[Sample], key: impl Fn(DateTime<Utc>) -> DateTime<Utc>) -> Vec<Stats> {
+    let mut stats = Vec::new();
+    let mut current: Option<(DateTime<Utc>, Accumulator)> = None;
+    let mut previous: Option<&Sample> = None;
+
+    for sample in samples {
+        let start = key(sample.recorded_at);
+
+        match &mut current {
+            Some((current_start, _)) if *current_start == start => {}
+            _ => {
+                if let Some((start, acc)) = current.take() {
+                    stats.push(acc.finish(start));
+                }
+                current = Some((start, Accumulator::default()));
+            }
+        }
+
+        if let Some((_, acc)) = &mut current {
+            acc.sample(sample);
+            if let Some(previous) = previous {
+                acc.leg(previous, sample);
+            }
+        }
+
+        previous = Some(sample);
+    }
+
+    if let Some((start, acc)) = current {
+        stats.push(acc.finish(start));
+    }
+
+    stats
+}
+
+/// Statistics per bucket, buckets without pings are left out
+pub fn buckets(samples: &[Sample], bucket: Bucket) -> Vec<Stats> {
+    summarize(samples, |t| bucket.start(t))
+}
+
+/// Statistics over all samples, starting at `start`
+pub fn total(samples: &[Sample], start: DateTime<Utc>) -> Stats {
+    summarize(samples, |_| start)
+        .pop()
+        .unwrap_or_else(|| Accumulator::default().finish(start))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::geo::{EARTH_RADIUS_M, Point};
+
+    /// Meters in a thousandth of a degree along the equator
+    const STEP_M: f64 = EARTH_RADIUS_M * 0.001 * std::f64::consts::PI / 180.0;
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, min, s).unwrap()
+    }
+
+    fn sample(recorded_at: DateTime<Utc>, lon: f64, speed: Option<f64>) -> Sample {
+        Sample {
+            recorded_at,
+            point: Point { lat: 0.0, lon },
+            speed,
+        }
+    }
+
+    /// `n` samples heading east along the equator, a thousandth of a degree every `every_s`
+    fn straight(n: usize, every_s: i64) -> Vec<Sample> {
+        let start = at(2024, 5, 1, 12, 0, 0);
+
+        (0..n)
+            .map(|i| {
+                let recorded_at = start + Duration::seconds(i as i64 * every_s);
+                sample(recorded_at, i as f64 * 0.001, None)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn measures_straight_track() {
+        let samples = straight(11, 10);
+        let stats = total(&samples, samples[0].recorded_at);
+
+        assert_eq!(stats.ping_count, 11);
+        assert!((stats.distance_m - 10.0 * STEP_M).abs() < 1e-6);
+        assert_eq!(stats.moving_time_s, 100);
+        assert!((stats.avg_speed - STEP_M / 10.0).abs() < 1e-6);
+        assert!((stats.max_speed - STEP_M / 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn totals_nothing_without_samples() {
+        let start = at(2024, 5, 1, 0, 0, 0);
+        let stats = total(&[], start);
+
+        assert_eq!(stats.start, start);
+        assert_eq!(stats.ping_count, 0);
+        assert_eq!(stats.distance_m, 0.0);
+        assert_eq!(stats.avg_speed, 0.0);
+    }
+
+    #[test]
+    fn starts_buckets_at_utc_midnight() {
+        // INFO: 2024-03-03 is a Sunday, 2024-03-04 a Monday
+        let sunday = at(2024, 3, 3, 23, 59, 59);
+        let monday = at(2024, 3, 4, 0, 0, 0);
+
+        assert_eq!(Bucket::Day.start(sunday), at(2024, 3, 3, 0, 0, 0));
+        assert_eq!(Bucket::Day.start(monday), monday);
+        assert_eq!(Bucket::Week.start(sunday), at(2024, 2, 26, 0, 0, 0));
+        assert_eq!(Bucket::Week.start(monday), monday);
+        assert_eq!(
+            Bucket::Month.start(at(2024, 2, 29, 23, 59, 59)),
+            at(2024, 2, 1, 0, 0, 0)
+        );
+        assert_eq!(
+            Bucket::Month.start(at(2024, 3, 1, 0, 0, 0)),
+            at(2024, 3, 1, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn counts_leg_across_boundary_towards_later_bucket() {
+        let samples = [
+            sample(at(2024, 2, 29, 23, 59, 50), 0.0, None),
+            sample(at(2024, 3, 1, 0, 0, 0), 0.001, None),
+        ];
+
+        let days = buckets(&samples, Bucket::Day);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].start, at(2024, 2, 29, 0, 0, 0));
+        assert_eq!(days[0].ping_count, 1);
+        assert_eq!(days[0].distance_m, 0.0);
+        assert_eq!(days[1].start, at(2024, 3, 1, 0, 0, 0));
+        assert_eq!(days[1].ping_count, 1);
+        assert!((days[1].distance_m - STEP_M).abs() < 1e-6);
+
+        let months = buckets(&samples, Bucket::Month);
+        assert_eq!(months.len(), 2);
+        assert_eq!(months[1].moving_time_s, 10);
+
+        // INFO: Thursday and Friday of the same week
+        let weeks = buckets(&samples, Bucket::Week);
+        assert_eq!(weeks.len(), 1);
+        assert_eq!(weeks[0].start, at(2024, 2, 26, 0, 0, 0));
+        assert_eq!(weeks[0].ping_count, 2);
+    }
+
+    #[test]
+    fn leaves_out_buckets_without_pings() {
+        let samples = [
+            sample(at(2024, 5, 1, 12, 0, 0), 0.0, None),
+            sample(at(2024, 5, 3, 12, 0, 0), 0.001, None),
+        ];
+
+        let days = buckets(&samples, Bucket::Day);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].start, at(2024, 5, 1, 0, 0, 0));
+        assert_eq!(days[1].start, at(2024, 5, 3, 0, 0, 0));
+    }
+
+    #[test]
+    fn separates_moving_from_standing_time() {
+        let start = at(2024, 5, 1, 12, 0, 0);
+        let samples = [
+            sample(start, 0.0, None),
+            // INFO: ~11 m/s for 10 s
+            sample(start + Duration::seconds(10), 0.001, None),
+            // INFO: Standing still for a minute
+            sample(start + Duration::seconds(70), 0.001, None),
+            // INFO: ~1 m of drift in 10 s stays below the moving speed
+            sample(start + Duration::seconds(80), 0.001_01, None),
+            // INFO: ~11 m/s for 10 s again
+            sample(start + Duration::seconds(90), 0.002_01, None),
+        ];
+
+        let stats = total(&samples, start);
+
+        assert_eq!(stats.moving_time_s, 20);
+        assert!((stats.distance_m - (2.0 * STEP_M + STEP_M / 100.0)).abs() < 1e-6);
+        assert!((stats.avg_speed - stats.distance_m / 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn prefers_reported_over_derived_max_speed() {
+        let mut samples = straight(3, 10);
+        let derived = STEP_M / 10.0;
+
+        let stats = total(&samples, samples[0].recorded_at);
+        assert!((stats.max_speed - derived).abs() < 1e-6);
+
+        // INFO: The device knows better, even when it reports less than the fixes suggest
+        samples[1].speed = Some(4.0);
+        samples[2].speed = Some(6.5);
+        let stats = total(&samples, samples[0].recorded_at);
+        assert_eq!(stats.max_speed, 6.5);
+        assert!(stats.max_speed < derived);
+    }
+
+    #[test]
+    fn parses_bucket_names() {
+        assert_eq!("Day".parse(), Ok(Bucket::Day));
+        assert_eq!("week".parse(), Ok(Bucket::Week));
+        assert_eq!("MONTH".parse(), Ok(Bucket::Month));
+        assert_eq!("year".parse::<Bucket>(), Err(()));
+    }
+}
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use super::Sample;
+use crate::geo::{self, Point};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    /// A silence longer than this ends a trip, unless the tracker stayed put
+    pub max_gap: Duration,
+    /// How far a tracker may wander around and still be stopped
+    pub dwell_radius_m: f64,
+    /// How long a tracker has to stay within the radius to be stopped
+    pub min_dwell: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            max_gap: Duration::minutes(10),
+            dwell_radius_m: 100.0,
+            min_dwell: Duration::minutes(5),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Trip {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub start: Point,
+    pub end: Point,
+    pub distance_m: f64,
+    pub duration_s: i64,
+    /// Meters per second, reported by the device when it does and derived from the fixes if not
+    pub max_speed: f64,
+    /// Meters per second over the whole duration
+    pub avg_speed: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Stop {
+    /// The centroid of the pings of the stop
+    pub point: Point,
+    pub arrived_at: DateTime<Utc>,
+    pub departed_at: DateTime<Utc>,
+    pub duration_s: i64,
+    pub ping_count: usize,
+}
+
+/// Finds the ranges of samples that stay within the dwell radius of their first sample for at
+/// least the minimum dwell time.
+fn stays(samples: &[Sample], options: &Options) -> Vec<(usize, usize)> {
+    let mut stays = Vec::new();
+    let mut i = 0;
+
+    while i < samples.len() {
+        let mut j = i;
+        while j + 1 < samples.len()
+            && geo::haversine(samples[i].point, samples[j + 1].point) <= options.dwell_radius_m
+        {
+            j += 1;
+        }
+
+        if j > i && samples[j].recorded_at - samples[i].recorded_at >= options.min_dwell {
+            stays.push((i, j));
+            i = j + 1;
+        } else {
+            i += 1;
+        }
+    }
+
+    stays
+}
+
+fn stop(samples: &[Sample]) -> Stop {
+    let n = samples.len() as f64;
+    let (first, last) = (samples[0], samples[samples.len() - 1]);
+
+    Stop {
+        point: Point {
+            lat: samples.iter().map(|s| s.point.lat).sum::<f64>() / n,
+            lon: samples.iter().map(|s| s.point.lon).sum::<f64>() / n,
+        },
+        arrived_at: first.recorded_at,
+        departed_at: last.recorded_at,
+        duration_s: (last.recorded_at - first.recorded_at).num_seconds(),
+        ping_count: samples.len(),
+    }
+}
+
+fn trip(samples: &[Sample]) -> Trip {
+    let (first, last) = (samples[0], samples[samples.len() - 1]);
+    let duration_s = (last.recorded_at - first.recorded_at).num_seconds();
+
+    let mut distance_m = 0.0;
+    let mut derived_max: f64 = 0.0;
+    for pair in samples.windows(2) {
+        let distance = geo::haversine(pair[0].point, pair[1].point);
+        let seconds = (pair[1].recorded_at - pair[0].recorded_at).num_milliseconds() as f64 / 1e3;
+
+        distance_m += distance;
+        if seconds > 0.0 {
+            derived_max = derived_max.max(distance / seconds);
+        }
+    }
+
+    let reported_max = samples
+        .iter()
+        .filter_map(|s| s.speed)
+        .max_by(f64::total_cmp);
+
+    Trip {
+        started_at: first.recorded_at,
+        ended_at: last.recorded_at,
+        start: first.point,
+        end: last.point,
+        distance_m,
+        duration_s,
+        max_speed: reported_max.unwrap_or(derived_max),
+        avg_speed: match duration_s {
+            0 => 0.0,
+            _ => distance_m / duration_s as f64,
+        },
+    }
+}
+
+/// Splits samples ordered by time into the trips between stops. A trip runs from the last
+/// sample of a stop to the first of the next and is cut wherever the tracker went silent.
+pub fn segment(samples: &[Sample], options: &Options) -> (Vec<Trip>, Vec<Stop>) {
+    let stays = stays(samples, options);
+    let stops = stays.iter().map(|&(a, b)| stop(&samples[a..=b])).collect();
+
+    let mut moving = Vec::new();
+    let mut start = 0;
+    for &(arrival, departure) in &stays {
+        if arrival > start {
+            moving.push((start, arrival));
+        }
+        start = departure;
+    }
+    if start + 1 < samples.len() {
+        moving.push((start, samples.len() - 1));
+    }
+
+    let mut trips = Vec::new();
+    for (start, end) in moving {
+        let mut from = start;
+
+        for i in start..end {
+            if samples[i + 1].recorded_at - samples[i].recorded_at > options.max_gap {
+                if i > from {
+                    trips.push(trip(&samples[from..=i]));
+                }
+                from = i + 1;
+            }
+        }
+
+        if end > from {
+            trips.push(trip(&samples[from..=end]));
+        }
+    }
+
+    (trips, stops)
+}
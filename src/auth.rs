@@ -2,6 +2,8 @@ use argon2::{
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
     password_hash::{SaltString, rand_core::OsRng},
 };
+use base64::{Engine, engine::general_purpose};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::Error;
@@ -38,3 +40,9 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
         .verify_password(password.as_bytes(), &hash)
         .is_ok()
 }
+
+pub fn generate_secret() -> String {
+    let mut secret_bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut secret_bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(secret_bytes)
+}
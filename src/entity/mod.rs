@@ -2,7 +2,17 @@
 
 pub mod prelude;
 
+pub mod alerts;
+pub mod geofence_events;
+pub mod geofence_trackers;
+pub mod geofences;
+pub mod import_jobs;
 pub mod pings;
+pub mod rules;
+pub mod sea_orm_active_enums;
+pub mod share_links;
 pub mod trackers;
 pub mod user_tokens;
 pub mod users;
+pub mod webhook_deliveries;
+pub mod webhooks;
@@ -1,8 +1,9 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.19
 
 use sea_orm::entity::prelude::*;
+use serde::Serialize;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize)]
 #[sea_orm(table_name = "pings")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -13,12 +14,26 @@ pub struct Model {
     #[sea_orm(column_type = "Double")]
     pub lon: f64,
     pub note: String,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub altitude: Option<f64>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub speed: Option<f64>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub heading: Option<f64>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub accuracy: Option<f64>,
+    pub satellites: Option<u8>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub battery: Option<f64>,
+    pub recorded_at: DateTimeUtc,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::geofence_events::Entity")]
+    GeofenceEvents,
     #[sea_orm(
         belongs_to = "super::trackers::Entity",
         from = "Column::TrackerId",
@@ -29,6 +44,12 @@ pub enum Relation {
     Trackers,
 }
 
+impl Related<super::geofence_events::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GeofenceEvents.def()
+    }
+}
+
 impl Related<super::trackers::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Trackers.def()
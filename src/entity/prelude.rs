@@ -1,6 +1,15 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.19
 
+pub use super::alerts::Entity as Alerts;
+pub use super::geofence_events::Entity as GeofenceEvents;
+pub use super::geofence_trackers::Entity as GeofenceTrackers;
+pub use super::geofences::Entity as Geofences;
+pub use super::import_jobs::Entity as ImportJobs;
 pub use super::pings::Entity as Pings;
+pub use super::rules::Entity as Rules;
+pub use super::share_links::Entity as ShareLinks;
 pub use super::trackers::Entity as Trackers;
 pub use super::user_tokens::Entity as UserTokens;
 pub use super::users::Entity as Users;
+pub use super::webhook_deliveries::Entity as WebhookDeliveries;
+pub use super::webhooks::Entity as Webhooks;
@@ -2,7 +2,7 @@
 
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "trackers")]
 pub struct Model {
     #[sea_orm(primary_key)]
@@ -11,14 +11,38 @@ pub struct Model {
     pub name: String,
     #[sea_orm(column_type = "Text")]
     pub desc: String,
+    pub secret: Option<String>,
+    #[sea_orm(unique)]
+    pub imei: Option<String>,
+    pub last_ping_id: Option<u64>,
+    pub last_ping_at: Option<DateTimeUtc>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub last_lat: Option<f64>,
+    #[sea_orm(column_type = "Double", nullable)]
+    pub last_lon: Option<f64>,
+    #[sea_orm(column_type = "Double")]
+    pub odometer_m: f64,
+    pub expected_interval_s: Option<u32>,
     pub created_at: DateTimeUtc,
     pub updated_at: DateTimeUtc,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::alerts::Entity")]
+    Alerts,
+    #[sea_orm(has_many = "super::geofence_events::Entity")]
+    GeofenceEvents,
+    #[sea_orm(has_many = "super::geofence_trackers::Entity")]
+    GeofenceTrackers,
+    #[sea_orm(has_many = "super::import_jobs::Entity")]
+    ImportJobs,
     #[sea_orm(has_many = "super::pings::Entity")]
     Pings,
+    #[sea_orm(has_many = "super::rules::Entity")]
+    Rules,
+    #[sea_orm(has_many = "super::share_links::Entity")]
+    ShareLinks,
     #[sea_orm(
         belongs_to = "super::users::Entity",
         from = "Column::UserId",
@@ -29,16 +53,61 @@ pub enum Relation {
     Users,
 }
 
+impl Related<super::alerts::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Alerts.def()
+    }
+}
+
+impl Related<super::geofence_events::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GeofenceEvents.def()
+    }
+}
+
+impl Related<super::geofence_trackers::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GeofenceTrackers.def()
+    }
+}
+
+impl Related<super::import_jobs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ImportJobs.def()
+    }
+}
+
 impl Related<super::pings::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Pings.def()
     }
 }
 
+impl Related<super::rules::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Rules.def()
+    }
+}
+
+impl Related<super::share_links::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ShareLinks.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
     }
 }
 
+impl Related<super::geofences::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::geofence_trackers::Relation::Geofences.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::geofence_trackers::Relation::Trackers.def().rev())
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
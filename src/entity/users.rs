@@ -18,10 +18,28 @@ pub struct Model {
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::geofences::Entity")]
+    Geofences,
+    #[sea_orm(has_many = "super::import_jobs::Entity")]
+    ImportJobs,
     #[sea_orm(has_many = "super::trackers::Entity")]
     Trackers,
     #[sea_orm(has_many = "super::user_tokens::Entity")]
     UserTokens,
+    #[sea_orm(has_many = "super::webhooks::Entity")]
+    Webhooks,
+}
+
+impl Related<super::geofences::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Geofences.def()
+    }
+}
+
+impl Related<super::import_jobs::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ImportJobs.def()
+    }
 }
 
 impl Related<super::trackers::Entity> for Entity {
@@ -36,4 +54,10 @@ impl Related<super::user_tokens::Entity> for Entity {
     }
 }
 
+impl Related<super::webhooks::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Webhooks.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
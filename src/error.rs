@@ -73,6 +73,12 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<axum::extract::multipart::MultipartError> for Error {
+    fn from(err: axum::extract::multipart::MultipartError) -> Self {
+        Self::BadRequest(err.body_text())
+    }
+}
+
 impl From<sea_orm::DbErr> for Error {
     fn from(err: sea_orm::DbErr) -> Self {
         Self::Internal(err.to_string())
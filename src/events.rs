@@ -0,0 +1,110 @@
+use serde::Serialize;
+
+use crate::{
+    AppState,
+    entity::{alerts, geofence_events, pings},
+};
+
+/// Events a subscriber may fall behind by before it starts missing some
+pub const CAPACITY: usize = 1024;
+
+/// The names of every kind of [`Event`]
+pub const NAMES: [&str; 6] = [
+    "ping",
+    "tracker_created",
+    "tracker_updated",
+    "tracker_deleted",
+    "geofence",
+    "alert",
+];
+
+/// Something that happened to a tracker, fanned out to everyone listening in-process
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    Ping {
+        #[serde(skip)]
+        user_id: u64,
+        tracker_id: u64,
+        ping: pings::Model,
+    },
+    TrackerCreated {
+        #[serde(skip)]
+        user_id: u64,
+        tracker_id: u64,
+        name: String,
+        desc: String,
+        imei: Option<String>,
+    },
+    TrackerUpdated {
+        #[serde(skip)]
+        user_id: u64,
+        tracker_id: u64,
+        name: String,
+        desc: String,
+        imei: Option<String>,
+    },
+    TrackerDeleted {
+        #[serde(skip)]
+        user_id: u64,
+        tracker_id: u64,
+    },
+    Geofence {
+        #[serde(skip)]
+        user_id: u64,
+        tracker_id: u64,
+        name: String,
+        event: geofence_events::Model,
+    },
+    Alert {
+        #[serde(skip)]
+        user_id: u64,
+        tracker_id: u64,
+        alert: alerts::Model,
+    },
+}
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ping { .. } => "ping",
+            Self::TrackerCreated { .. } => "tracker_created",
+            Self::TrackerUpdated { .. } => "tracker_updated",
+            Self::TrackerDeleted { .. } => "tracker_deleted",
+            Self::Geofence { .. } => "geofence",
+            Self::Alert { .. } => "alert",
+        }
+    }
+
+    /// The owner of the tracker the event is about
+    pub fn user_id(&self) -> u64 {
+        match self {
+            Self::Ping { user_id, .. }
+            | Self::TrackerCreated { user_id, .. }
+            | Self::TrackerUpdated { user_id, .. }
+            | Self::TrackerDeleted { user_id, .. }
+            | Self::Geofence { user_id, .. }
+            | Self::Alert { user_id, .. } => *user_id,
+        }
+    }
+
+    pub fn tracker_id(&self) -> u64 {
+        match self {
+            Self::Ping { tracker_id, .. }
+            | Self::TrackerCreated { tracker_id, .. }
+            | Self::TrackerUpdated { tracker_id, .. }
+            | Self::TrackerDeleted { tracker_id, .. }
+            | Self::Geofence { tracker_id, .. }
+            | Self::Alert { tracker_id, .. } => *tracker_id,
+        }
+    }
+}
+
+/// Sends the event to the live streams and wakes the webhook dispatcher. Its deliveries must
+/// have been queued with [`crate::webhooks::enqueue`] already, live streams may miss events but
+/// webhooks must not.
+pub fn publish(state: &AppState, event: Event) {
+    // INFO: Sending only fails when nobody is subscribed, which is fine
+    let _ = state.events.send(event);
+    state.deliveries.notify_one();
+}
@@ -0,0 +1,127 @@
+use chrono::SecondsFormat;
+
+use super::Writer;
+use crate::entity::pings;
+
+/// RFC 4180 rows with one ping per line
+pub struct Csv;
+
+fn escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+impl Writer for Csv {
+    fn header(&mut self) -> String {
+        "id,tracker_id,recorded_at,lat,lon,altitude,speed,heading,accuracy,satellites,battery,note\r\n"
+            .into()
+    }
+
+    fn ping(&mut self, ping: &pings::Model) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}\r\n",
+            ping.id,
+            ping.tracker_id,
+            ping.recorded_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ping.lat,
+            ping.lon,
+            optional(ping.altitude),
+            optional(ping.speed),
+            optional(ping.heading),
+            optional(ping.accuracy),
+            optional(ping.satellites),
+            optional(ping.battery),
+            escape(&ping.note),
+        )
+    }
+
+    fn footer(&mut self) -> String {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+
+    use crate::export::{
+        Format,
+        tests::{pings, render},
+    };
+
+    /// Splits RFC 4180 text into records of unquoted fields
+    fn records(text: &str) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') => quoted = true,
+                (false, ',') => record.push(std::mem::take(&mut field)),
+                (false, '\r') => {}
+                (false, '\n') => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                (false, c) => field.push(c),
+            }
+        }
+
+        records
+    }
+
+    /// An empty field is a missing reading
+    fn optional<T: std::str::FromStr>(value: &str) -> Option<T> {
+        value.parse().ok()
+    }
+
+    #[test]
+    fn round_trips_pings() {
+        let pings = pings();
+        let text = render(Format::Csv, &pings);
+
+        assert!(text.ends_with("\r\n"));
+
+        let records = records(&text);
+        assert_eq!(
+            records[0].join(","),
+            "id,tracker_id,recorded_at,lat,lon,altitude,speed,heading,accuracy,satellites,battery,note"
+        );
+        assert_eq!(records.len(), pings.len() + 1);
+
+        for (record, ping) in records[1..].iter().zip(&pings) {
+            assert_eq!(record.len(), 12);
+            assert_eq!(record[0].parse::<u64>().unwrap(), ping.id);
+            assert_eq!(record[1].parse::<u64>().unwrap(), ping.tracker_id);
+            assert_eq!(
+                record[2].parse::<DateTime<Utc>>().unwrap(),
+                ping.recorded_at
+            );
+            assert_eq!(record[3].parse::<f64>().unwrap(), ping.lat);
+            assert_eq!(record[4].parse::<f64>().unwrap(), ping.lon);
+            assert_eq!(optional(&record[5]), ping.altitude);
+            assert_eq!(optional(&record[6]), ping.speed);
+            assert_eq!(optional(&record[7]), ping.heading);
+            assert_eq!(optional(&record[8]), ping.accuracy);
+            assert_eq!(optional(&record[9]), ping.satellites);
+            assert_eq!(optional(&record[10]), ping.battery);
+            assert_eq!(record[11], ping.note);
+        }
+    }
+}
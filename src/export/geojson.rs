@@ -0,0 +1,198 @@
+use chrono::SecondsFormat;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+
+use super::{LINE_POINTS, Writer};
+use crate::entity::pings;
+
+/// A FeatureCollection with a Point feature per ping and LineString features tracing each tracker
+pub struct GeoJson {
+    names: HashMap<u64, String>,
+    tracker_id: Option<u64>,
+    line: Vec<[f64; 2]>,
+    empty: bool,
+}
+
+impl GeoJson {
+    pub fn new(names: HashMap<u64, String>) -> Self {
+        Self {
+            names,
+            tracker_id: None,
+            line: Vec::new(),
+            empty: true,
+        }
+    }
+
+    fn push(&mut self, out: &mut String, feature: Value) {
+        if !self.empty {
+            out.push(',');
+        }
+
+        out.push_str(&feature.to_string());
+        out.push('\n');
+        self.empty = false;
+    }
+
+    fn flush_line(&mut self, out: &mut String) {
+        let line = std::mem::take(&mut self.line);
+        let Some(tracker_id) = self.tracker_id else {
+            return;
+        };
+
+        if line.len() < 2 {
+            return;
+        }
+
+        let feature = json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "LineString",
+                "coordinates": line,
+            },
+            "properties": {
+                "tracker_id": tracker_id,
+                "name": self.names.get(&tracker_id),
+            },
+        });
+
+        self.push(out, feature);
+    }
+}
+
+impl Writer for GeoJson {
+    fn header(&mut self) -> String {
+        "{\"type\":\"FeatureCollection\",\"features\":[\n".into()
+    }
+
+    fn ping(&mut self, ping: &pings::Model) -> String {
+        let mut out = String::new();
+
+        if self.tracker_id != Some(ping.tracker_id) {
+            self.flush_line(&mut out);
+            self.tracker_id = Some(ping.tracker_id);
+        }
+
+        let coordinates = [ping.lon, ping.lat];
+        self.line.push(coordinates);
+
+        let feature = json!({
+            "type": "Feature",
+            "id": ping.id,
+            "geometry": {
+                "type": "Point",
+                "coordinates": [ping.lon, ping.lat],
+            },
+            "properties": {
+                "tracker_id": ping.tracker_id,
+                "note": ping.note,
+                "altitude": ping.altitude,
+                "speed": ping.speed,
+                "heading": ping.heading,
+                "accuracy": ping.accuracy,
+                "satellites": ping.satellites,
+                "battery": ping.battery,
+                "recorded_at": ping.recorded_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            },
+        });
+
+        self.push(&mut out, feature);
+
+        // INFO: The next part of the line starts where this one ends
+        if self.line.len() == LINE_POINTS {
+            self.flush_line(&mut out);
+            self.line.push(coordinates);
+        }
+
+        out
+    }
+
+    fn footer(&mut self) -> String {
+        let mut out = String::new();
+        self.flush_line(&mut out);
+        out.push_str("]}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::{
+        export::{
+            Format,
+            tests::{assert_fix, assert_parts, pings, render, track},
+        },
+        import,
+    };
+
+    #[test]
+    fn round_trips_pings() {
+        let pings = pings();
+        let text = render(Format::GeoJson, &pings);
+
+        let fixes = import::geojson::parse(&text).unwrap();
+        assert_eq!(fixes.len(), pings.len());
+
+        for (fix, ping) in fixes.iter().zip(&pings) {
+            assert_fix(fix, ping);
+            assert_eq!(fix.speed, ping.speed);
+            assert_eq!(fix.heading, ping.heading);
+            assert_eq!(fix.accuracy, ping.accuracy);
+            assert_eq!(fix.satellites, ping.satellites);
+            assert_eq!(fix.battery, ping.battery);
+        }
+    }
+
+    #[test]
+    fn draws_a_line_per_tracker() {
+        let pings = pings();
+        let document: Value = serde_json::from_str(&render(Format::GeoJson, &pings)).unwrap();
+
+        let lines: Vec<_> = document["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|feature| feature["geometry"]["type"] == "LineString")
+            .collect();
+        assert_eq!(lines.len(), 2);
+
+        for (line, (tracker_id, name)) in lines.iter().zip([(7, "Van <1>"), (9, "Boat & co")]) {
+            assert_eq!(line["properties"]["tracker_id"], tracker_id);
+            assert_eq!(line["properties"]["name"], name);
+
+            let expected: Vec<_> = pings
+                .iter()
+                .filter(|ping| ping.tracker_id == tracker_id)
+                .map(|ping| serde_json::json!([ping.lon, ping.lat]))
+                .collect();
+            assert_eq!(line["geometry"]["coordinates"], Value::Array(expected));
+        }
+    }
+
+    #[test]
+    fn splits_long_lines() {
+        let track = track();
+        let document: Value = serde_json::from_str(&render(Format::GeoJson, &track)).unwrap();
+
+        let parts: Vec<Vec<[f64; 2]>> = document["features"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|feature| feature["geometry"]["type"] == "LineString")
+            .map(|feature| serde_json::from_value(feature["geometry"]["coordinates"].clone()))
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(parts.len(), 3);
+        assert_parts(&parts, &track);
+    }
+
+    #[test]
+    fn writes_empty_collection() {
+        let document: Value = serde_json::from_str(&render(Format::GeoJson, &[])).unwrap();
+
+        assert_eq!(document["type"], "FeatureCollection");
+        assert_eq!(document["features"], Value::Array(Vec::new()));
+    }
+}
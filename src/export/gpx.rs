@@ -0,0 +1,106 @@
+use chrono::SecondsFormat;
+use std::collections::HashMap;
+
+use super::{Writer, escape};
+use crate::entity::pings;
+
+/// A GPX 1.1 document with one track per tracker
+pub struct Gpx {
+    names: HashMap<u64, String>,
+    tracker_id: Option<u64>,
+}
+
+impl Gpx {
+    pub fn new(names: HashMap<u64, String>) -> Self {
+        Self {
+            names,
+            tracker_id: None,
+        }
+    }
+}
+
+impl Writer for Gpx {
+    fn header(&mut self) -> String {
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<gpx version="1.1" creator="dracker" xmlns="http://www.topografix.com/GPX/1/1">"#,
+            "\n"
+        )
+        .into()
+    }
+
+    fn ping(&mut self, ping: &pings::Model) -> String {
+        let mut point = String::new();
+
+        if self.tracker_id != Some(ping.tracker_id) {
+            if self.tracker_id.is_some() {
+                point.push_str("</trkseg></trk>\n");
+            }
+
+            let name = self
+                .names
+                .get(&ping.tracker_id)
+                .cloned()
+                .unwrap_or_default();
+            point.push_str(&format!("<trk><name>{}</name><trkseg>\n", escape(&name)));
+            self.tracker_id = Some(ping.tracker_id);
+        }
+
+        point.push_str(&format!(r#"<trkpt lat="{}" lon="{}">"#, ping.lat, ping.lon));
+
+        if let Some(altitude) = ping.altitude {
+            point.push_str(&format!("<ele>{altitude}</ele>"));
+        }
+
+        point.push_str(&format!(
+            "<time>{}</time>",
+            ping.recorded_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ));
+
+        if !ping.note.is_empty() {
+            point.push_str(&format!("<desc>{}</desc>", escape(&ping.note)));
+        }
+
+        if let Some(satellites) = ping.satellites {
+            point.push_str(&format!("<sat>{satellites}</sat>"));
+        }
+
+        point.push_str("</trkpt>\n");
+        point
+    }
+
+    fn footer(&mut self) -> String {
+        match self.tracker_id {
+            Some(_) => "</trkseg></trk>\n</gpx>\n".into(),
+            None => "</gpx>\n".into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        export::{
+            Format,
+            tests::{assert_fix, pings, render},
+        },
+        import,
+    };
+
+    #[test]
+    fn round_trips_pings() {
+        let pings = pings();
+        let text = render(Format::Gpx, &pings);
+
+        assert_eq!(text.matches("<trk>").count(), 2);
+
+        let fixes = import::gpx::parse(&text).unwrap();
+        assert_eq!(fixes.len(), pings.len());
+
+        for (fix, ping) in fixes.iter().zip(&pings) {
+            assert_fix(fix, ping);
+            assert_eq!(fix.satellites, ping.satellites);
+        }
+    }
+}
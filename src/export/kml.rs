@@ -0,0 +1,201 @@
+use chrono::SecondsFormat;
+use std::collections::HashMap;
+
+use super::{LINE_POINTS, Writer, escape};
+use crate::entity::pings;
+
+/// A KML 2.2 document with a folder of placemarks and their path per tracker
+pub struct Kml {
+    names: HashMap<u64, String>,
+    tracker_id: Option<u64>,
+    line: Vec<String>,
+}
+
+impl Kml {
+    pub fn new(names: HashMap<u64, String>) -> Self {
+        Self {
+            names,
+            tracker_id: None,
+            line: Vec::new(),
+        }
+    }
+
+    fn flush_line(&mut self, out: &mut String) {
+        let line = std::mem::take(&mut self.line);
+
+        if line.len() >= 2 {
+            out.push_str(&format!(
+                "<Placemark><name>Path</name><LineString><coordinates>{}</coordinates></LineString></Placemark>\n",
+                line.join(" ")
+            ));
+        }
+    }
+
+    fn close_folder(&mut self, out: &mut String) {
+        if self.tracker_id.is_none() {
+            return;
+        }
+
+        self.flush_line(out);
+        out.push_str("</Folder>\n");
+    }
+}
+
+impl Writer for Kml {
+    fn header(&mut self) -> String {
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<kml xmlns="http://www.opengis.net/kml/2.2"><Document><name>dracker</name>"#,
+            "\n"
+        )
+        .into()
+    }
+
+    fn ping(&mut self, ping: &pings::Model) -> String {
+        let mut out = String::new();
+
+        if self.tracker_id != Some(ping.tracker_id) {
+            self.close_folder(&mut out);
+
+            let name = self
+                .names
+                .get(&ping.tracker_id)
+                .cloned()
+                .unwrap_or_default();
+            out.push_str(&format!("<Folder><name>{}</name>\n", escape(&name)));
+            self.tracker_id = Some(ping.tracker_id);
+        }
+
+        let point = format!("{},{}", ping.lon, ping.lat);
+        self.line.push(point.clone());
+
+        let coordinates = match ping.altitude {
+            Some(altitude) => format!("{},{},{altitude}", ping.lon, ping.lat),
+            None => format!("{},{}", ping.lon, ping.lat),
+        };
+
+        out.push_str("<Placemark>");
+
+        if !ping.note.is_empty() {
+            out.push_str(&format!(
+                "<description>{}</description>",
+                escape(&ping.note)
+            ));
+        }
+
+        out.push_str(&format!(
+            "<TimeStamp><when>{}</when></TimeStamp><Point><coordinates>{coordinates}</coordinates></Point></Placemark>\n",
+            ping.recorded_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+        ));
+
+        // INFO: The next part of the path starts where this one ends
+        if self.line.len() == LINE_POINTS {
+            self.flush_line(&mut out);
+            self.line.push(point);
+        }
+
+        out
+    }
+
+    fn footer(&mut self) -> String {
+        let mut out = String::new();
+        self.close_folder(&mut out);
+        out.push_str("</Document></kml>\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quick_xml::{Reader, events::Event};
+
+    use crate::{
+        export::{
+            Format,
+            tests::{assert_fix, assert_parts, pings, render, track},
+        },
+        import,
+    };
+
+    /// The text of every element named `element` inside one named `parent`
+    fn texts(text: &str, parent: &[u8], element: &[u8]) -> Vec<String> {
+        let mut reader = Reader::from_str(text);
+        let mut path: Vec<Vec<u8>> = Vec::new();
+        let mut texts = Vec::new();
+
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(e) => path.push(e.local_name().as_ref().to_vec()),
+                Event::End(_) => {
+                    path.pop();
+                }
+                Event::Text(e) => {
+                    if let [.., p, el] = path.as_slice()
+                        && p == parent
+                        && el == element
+                    {
+                        texts.push(e.unescape().unwrap().into_owned());
+                    }
+                }
+                Event::Eof => return texts,
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_pings() {
+        let pings = pings();
+        let text = render(Format::Kml, &pings);
+
+        let fixes = import::kml::parse(&text).unwrap();
+        assert_eq!(fixes.len(), pings.len());
+
+        for (fix, ping) in fixes.iter().zip(&pings) {
+            assert_fix(fix, ping);
+        }
+    }
+
+    #[test]
+    fn draws_a_folder_and_path_per_tracker() {
+        let pings = pings();
+        let text = render(Format::Kml, &pings);
+
+        assert_eq!(texts(&text, b"Folder", b"name"), ["Van <1>", "Boat & co"]);
+
+        let paths = texts(&text, b"LineString", b"coordinates");
+        assert_eq!(paths.len(), 2);
+
+        for (path, tracker_id) in paths.iter().zip([7, 9]) {
+            let expected: Vec<_> = pings
+                .iter()
+                .filter(|ping| ping.tracker_id == tracker_id)
+                .map(|ping| format!("{},{}", ping.lon, ping.lat))
+                .collect();
+            assert_eq!(path.split(' ').collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn splits_long_paths() {
+        let track = track();
+        let text = render(Format::Kml, &track);
+
+        let parts: Vec<Vec<[f64; 2]>> = texts(&text, b"LineString", b"coordinates")
+            .iter()
+            .map(|path| {
+                path.split(' ')
+                    .map(|point| {
+                        let (lon, lat) = point.split_once(',').unwrap();
+                        [lon.parse().unwrap(), lat.parse().unwrap()]
+                    })
+                    .collect()
+            })
+            .collect();
+
+        assert_eq!(parts.len(), 3);
+        assert_parts(&parts, &track);
+        assert_eq!(text.matches("<Folder>").count(), 1);
+    }
+}
@@ -0,0 +1,264 @@
+use axum::body::Body;
+use futures::StreamExt;
+use sea_orm::{DatabaseConnection, DbErr, Select};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use tokio::sync::mpsc;
+use tracing::error;
+
+use crate::entity::{pings, prelude::Pings};
+
+pub mod csv;
+pub mod geojson;
+pub mod gpx;
+pub mod kml;
+
+/// Points in a path before writers that draw one split it, so they hold at most this many
+pub const LINE_POINTS: usize = 10_000;
+
+/// Renders a document one ping at a time so exports never hold a whole track in memory.
+/// Pings arrive grouped by tracker and in the order they were recorded. Paths drawn alongside
+/// the pings are written in parts of up to [`LINE_POINTS`] points that join end to start.
+pub trait Writer: Send {
+    fn header(&mut self) -> String;
+    fn ping(&mut self, ping: &pings::Model) -> String;
+    fn footer(&mut self) -> String;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    Csv,
+    GeoJson,
+    Gpx,
+    Kml,
+}
+
+impl FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "geojson" | "json" => Ok(Self::GeoJson),
+            "gpx" => Ok(Self::Gpx),
+            "kml" => Ok(Self::Kml),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Format {
+    /// Picks the first supported media type of an `Accept` header
+    pub fn from_accept(accept: &str) -> Option<Self> {
+        accept
+            .split(',')
+            .filter_map(|media| media.split(';').next())
+            .find_map(|media| match media.trim() {
+                "text/csv" => Some(Self::Csv),
+                "application/geo+json" | "application/json" => Some(Self::GeoJson),
+                "application/gpx+xml" => Some(Self::Gpx),
+                "application/vnd.google-earth.kml+xml" => Some(Self::Kml),
+                _ => None,
+            })
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv",
+            Self::GeoJson => "application/geo+json",
+            Self::Gpx => "application/gpx+xml",
+            Self::Kml => "application/vnd.google-earth.kml+xml",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::GeoJson => "geojson",
+            Self::Gpx => "gpx",
+            Self::Kml => "kml",
+        }
+    }
+
+    /// `names` maps tracker ids to the names used for their tracks
+    pub fn writer(&self, names: HashMap<u64, String>) -> Box<dyn Writer> {
+        match self {
+            Self::Csv => Box::new(csv::Csv),
+            Self::GeoJson => Box::new(geojson::GeoJson::new(names)),
+            Self::Gpx => Box::new(gpx::Gpx::new(names)),
+            Self::Kml => Box::new(kml::Kml::new(names)),
+        }
+    }
+}
+
+pub fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Streams the pings selected by `query` through `writer` into a response body, leaving out
+/// any not in `keep` when given.
+pub fn stream(
+    db: DatabaseConnection,
+    query: Select<Pings>,
+    keep: Option<HashSet<u64>>,
+    mut writer: Box<dyn Writer>,
+) -> Body {
+    let (tx, rx) = mpsc::channel::<Result<String, DbErr>>(16);
+
+    tokio::spawn(async move {
+        let mut rows = match query.stream(&db).await {
+            Ok(rows) => rows,
+            Err(err) => {
+                error!("{err}");
+                let _ = tx.send(Err(err)).await;
+                return;
+            }
+        };
+
+        if tx.send(Ok(writer.header())).await.is_err() {
+            return;
+        }
+
+        while let Some(row) = rows.next().await {
+            let chunk = match row {
+                Ok(ping) if keep.as_ref().is_some_and(|keep| !keep.contains(&ping.id)) => continue,
+                Ok(ping) => Ok(writer.ping(&ping)),
+                Err(err) => {
+                    error!("{err}");
+                    Err(err)
+                }
+            };
+
+            let failed = chunk.is_err();
+            if tx.send(chunk).await.is_err() || failed {
+                return;
+            }
+        }
+
+        let _ = tx.send(Ok(writer.footer())).await;
+    });
+
+    Body::from_stream(futures::stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|chunk| (chunk, rx))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::ingest::Fix;
+
+    fn ping(id: u64, tracker_id: u64, lat: f64, lon: f64, note: &str) -> pings::Model {
+        let recorded_at = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+            + chrono::Duration::seconds(id as i64 * 30);
+
+        pings::Model {
+            id,
+            tracker_id,
+            lat,
+            lon,
+            note: note.into(),
+            altitude: None,
+            speed: None,
+            heading: None,
+            accuracy: None,
+            satellites: None,
+            battery: None,
+            recorded_at,
+            created_at: recorded_at,
+            updated_at: recorded_at,
+        }
+    }
+
+    /// Two trackers, with notes that need escaping and readings that are missing
+    pub fn pings() -> Vec<pings::Model> {
+        vec![
+            pings::Model {
+                altitude: Some(12.5),
+                speed: Some(3.25),
+                heading: Some(270.0),
+                accuracy: Some(4.0),
+                satellites: Some(9),
+                battery: Some(87.0),
+                ..ping(1, 7, 52.520_008, 13.404_954, "Left \"home\", at last")
+            },
+            ping(2, 7, 52.521_1, 13.405_5, ""),
+            ping(3, 7, -33.868_82, 151.209_296, "Fish & <chips>"),
+            ping(4, 9, 0.0, -179.999_5, "Line one\nline two"),
+            pings::Model {
+                altitude: Some(-3.0),
+                ..ping(5, 9, -0.000_1, 179.999_5, "")
+            },
+        ]
+    }
+
+    /// A single tracker heading east along the equator, long enough to split its path twice.
+    /// The longitudes are exact in binary so they survive a trip through JSON unchanged.
+    pub fn track() -> Vec<pings::Model> {
+        (1..=2 * LINE_POINTS as u64 + 1)
+            .map(|id| ping(id, 7, 0.0, id as f64 / 1024.0, ""))
+            .collect()
+    }
+
+    /// Checks that `parts` join into the path of `pings` without any part holding too many
+    pub fn assert_parts(parts: &[Vec<[f64; 2]>], pings: &[pings::Model]) {
+        assert!(parts.iter().all(|part| part.len() <= LINE_POINTS));
+
+        for pair in parts.windows(2) {
+            assert_eq!(pair[0].last(), pair[1].first());
+        }
+
+        let mut joined = parts[0].clone();
+        for part in &parts[1..] {
+            joined.extend_from_slice(&part[1..]);
+        }
+
+        let expected: Vec<_> = pings.iter().map(|ping| [ping.lon, ping.lat]).collect();
+        assert_eq!(joined, expected);
+    }
+
+    pub fn names() -> HashMap<u64, String> {
+        HashMap::from([(7, "Van <1>".into()), (9, "Boat & co".into())])
+    }
+
+    /// Runs `pings` through a writer the way [`stream`] does
+    pub fn render(format: Format, pings: &[pings::Model]) -> String {
+        let mut writer = format.writer(names());
+        let mut out = writer.header();
+
+        for ping in pings {
+            out.push_str(&writer.ping(ping));
+        }
+
+        out.push_str(&writer.footer());
+        out
+    }
+
+    /// Compares what an importer read back with the readings the format carries
+    pub fn assert_fix(fix: &Fix, ping: &pings::Model) {
+        assert_eq!(fix.lat, ping.lat);
+        assert_eq!(fix.lon, ping.lon);
+        assert_eq!(fix.altitude, ping.altitude);
+        assert_eq!(fix.note, ping.note);
+        assert_eq!(fix.recorded_at, Some(ping.recorded_at));
+    }
+
+    #[test]
+    fn parses_formats_and_accept_headers() {
+        assert_eq!("GeoJSON".parse(), Ok(Format::GeoJson));
+        assert_eq!("json".parse(), Ok(Format::GeoJson));
+        assert_eq!("shp".parse::<Format>(), Err(()));
+        assert_eq!(
+            Format::from_accept("text/html, application/gpx+xml;q=0.9, text/csv"),
+            Some(Format::Gpx)
+        );
+        assert_eq!(Format::from_accept("text/html"), None);
+    }
+}
@@ -0,0 +1,402 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Mean earth radius as used by the haversine formula
+pub const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// Whether `lat`,`lon` are within the ranges of coordinates in degrees
+pub fn valid(lat: f64, lon: f64) -> bool {
+    (-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)
+}
+
+/// Rounds a coordinate to `places` decimals, 4 places are about 11 m at the equator
+pub fn round(degrees: f64, places: u8) -> f64 {
+    let scale = 10f64.powi(places.into());
+    (degrees * scale).round() / scale
+}
+
+/// Great-circle distance in meters between two points
+pub fn haversine(a: Point, b: Point) -> f64 {
+    let d_lat = (b.lat - a.lat).to_radians();
+    let d_lon = (b.lon - a.lon).to_radians();
+
+    let h = (d_lat / 2.0).sin().powi(2)
+        + a.lat.to_radians().cos() * b.lat.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS_M * h.sqrt().min(1.0).asin()
+}
+
+/// A box in degrees, `min_lon > max_lon` when it crosses the antimeridian
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bbox {
+    pub min_lon: f64,
+    pub min_lat: f64,
+    pub max_lon: f64,
+    pub max_lat: f64,
+}
+
+impl Bbox {
+    /// The smallest box containing every point within `radius_m` of `lat`,`lon`
+    pub fn around(lat: f64, lon: f64, radius_m: f64) -> Self {
+        let d_lat = (radius_m / EARTH_RADIUS_M).to_degrees();
+        let (min_lat, max_lat) = (lat - d_lat, lat + d_lat);
+
+        // INFO: A circle over a pole covers every longitude
+        if min_lat <= -90.0 || max_lat >= 90.0 {
+            return Self {
+                min_lon: -180.0,
+                min_lat: min_lat.max(-90.0),
+                max_lon: 180.0,
+                max_lat: max_lat.min(90.0),
+            };
+        }
+
+        let d_lon = (radius_m / (EARTH_RADIUS_M * lat.to_radians().cos())).to_degrees();
+        if d_lon >= 180.0 {
+            return Self {
+                min_lon: -180.0,
+                min_lat,
+                max_lon: 180.0,
+                max_lat,
+            };
+        }
+
+        let wrap = |lon: f64| match lon {
+            lon if lon < -180.0 => lon + 360.0,
+            lon if lon > 180.0 => lon - 360.0,
+            lon => lon,
+        };
+
+        Self {
+            min_lon: wrap(lon - d_lon),
+            min_lat,
+            max_lon: wrap(lon + d_lon),
+            max_lat,
+        }
+    }
+
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.min_lon > self.max_lon
+    }
+}
+
+/// Length of a degree of latitude
+const METERS_PER_DEGREE: f64 = EARTH_RADIUS_M * std::f64::consts::PI / 180.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// Planar coordinates in meters around the first point, accurate enough over the length of a track
+fn project(points: &[Point]) -> Vec<(f64, f64)> {
+    let Some(origin) = points.first() else {
+        return Vec::new();
+    };
+
+    let cos = origin.lat.to_radians().cos();
+
+    points
+        .iter()
+        .map(|p| {
+            // INFO: Longitudes are taken relative to the origin so tracks can cross the antimeridian
+            let d_lon = (p.lon - origin.lon + 540.0).rem_euclid(360.0) - 180.0;
+            (
+                d_lon * METERS_PER_DEGREE * cos,
+                (p.lat - origin.lat) * METERS_PER_DEGREE,
+            )
+        })
+        .collect()
+}
+
+/// Whether `p` lies inside `polygon` by ray casting, with longitudes taken relative to the first
+/// vertex so polygons may cross the antimeridian
+pub fn contains(polygon: &[Point], p: Point) -> bool {
+    let Some(origin) = polygon.first() else {
+        return false;
+    };
+
+    let relative = |q: &Point| {
+        (
+            (q.lon - origin.lon + 540.0).rem_euclid(360.0) - 180.0,
+            q.lat,
+        )
+    };
+    let (x, y) = relative(&p);
+
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+
+    for i in 0..polygon.len() {
+        let (xi, yi) = relative(&polygon[i]);
+        let (xj, yj) = relative(&polygon[j]);
+
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+
+        j = i;
+    }
+
+    inside
+}
+
+/// Distance from `p` to the segment between `a` and `b`
+fn segment_distance(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+
+    let t = if length > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    (p.0 - a.0 - t * dx).hypot(p.1 - a.1 - t * dy)
+}
+
+fn triangle_area(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    ((b.0 - a.0) * (c.1 - a.1) - (c.0 - a.0) * (b.1 - a.1)).abs() / 2.0
+}
+
+/// Ramer-Douglas-Peucker, returns the indices of the points that deviate more than
+/// `tolerance_m` from the simplified line, always including both ends.
+pub fn douglas_peucker(points: &[Point], tolerance_m: f64) -> Vec<usize> {
+    let n = points.len();
+    if n < 3 {
+        return (0..n).collect();
+    }
+
+    let xy = project(points);
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    keep[n - 1] = true;
+
+    // INFO: An explicit stack as tracks can be long enough to overflow a recursive one
+    let mut stack = vec![(0, n - 1)];
+    while let Some((first, last)) = stack.pop() {
+        let (mut index, mut max) = (first, 0.0);
+
+        for (i, &p) in xy.iter().enumerate().take(last).skip(first + 1) {
+            let distance = segment_distance(p, xy[first], xy[last]);
+            if distance > max {
+                (index, max) = (i, distance);
+            }
+        }
+
+        if max > tolerance_m {
+            keep[index] = true;
+            stack.push((first, index));
+            stack.push((index, last));
+        }
+    }
+
+    (0..n).filter(|&i| keep[i]).collect()
+}
+
+struct Candidate {
+    area: f64,
+    index: usize,
+    version: u32,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    // INFO: Reversed so the max-heap pops the smallest area first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .area
+            .total_cmp(&self.area)
+            .then_with(|| other.index.cmp(&self.index))
+    }
+}
+
+/// Visvalingam-Whyatt, drops the point spanning the smallest triangle with its neighbours
+/// until at most `max_points` are left. Returns the indices of the kept points.
+pub fn visvalingam(points: &[Point], max_points: usize) -> Vec<usize> {
+    let n = points.len();
+    let max_points = max_points.max(2);
+    if n <= max_points {
+        return (0..n).collect();
+    }
+
+    let xy = project(points);
+    let mut prev: Vec<usize> = (0..n).map(|i| i.saturating_sub(1)).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1).min(n - 1)).collect();
+    let mut version = vec![0; n];
+    let mut removed = vec![false; n];
+
+    let mut heap: BinaryHeap<Candidate> = (1..n - 1)
+        .map(|i| Candidate {
+            area: triangle_area(xy[i - 1], xy[i], xy[i + 1]),
+            index: i,
+            version: 0,
+        })
+        .collect();
+
+    let mut remaining = n;
+    while remaining > max_points {
+        let Some(candidate) = heap.pop() else {
+            break;
+        };
+
+        // INFO: Entries are never updated in place, outdated ones are skipped here instead
+        if removed[candidate.index] || candidate.version != version[candidate.index] {
+            continue;
+        }
+
+        removed[candidate.index] = true;
+        remaining -= 1;
+
+        let (p, q) = (prev[candidate.index], next[candidate.index]);
+        next[p] = q;
+        prev[q] = p;
+
+        for i in [p, q] {
+            if i == 0 || i == n - 1 {
+                continue;
+            }
+
+            version[i] += 1;
+            heap.push(Candidate {
+                // INFO: Never below the area just removed, so removals happen in a stable order
+                area: triangle_area(xy[prev[i]], xy[i], xy[next[i]]).max(candidate.area),
+                index: i,
+                version: version[i],
+            });
+        }
+    }
+
+    (0..n).filter(|&i| !removed[i]).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(lat: f64, lon: f64) -> Point {
+        Point { lat, lon }
+    }
+
+    /// A straight line of `n` points heading east along the equator from `lon`
+    fn line(n: usize, lon: f64) -> Vec<Point> {
+        (0..n).map(|i| point(0.0, lon + i as f64 * 0.001)).collect()
+    }
+
+    /// A zigzag of `n` points that deviates ~110 m to either side of the equator
+    fn zigzag(n: usize) -> Vec<Point> {
+        (0..n)
+            .map(|i| point(if i % 2 == 0 { 0.001 } else { -0.001 }, i as f64 * 0.001))
+            .collect()
+    }
+
+    #[test]
+    fn haversine_of_a_degree_along_the_equator() {
+        let d = haversine(point(0.0, 0.0), point(0.0, 1.0));
+        assert!((d - 111_195.08).abs() < 0.1, "{d}");
+    }
+
+    #[test]
+    fn short_tracks_are_kept_whole() {
+        for n in 0..3 {
+            let points = line(n, 0.0);
+            let all: Vec<usize> = (0..n).collect();
+
+            assert_eq!(douglas_peucker(&points, 1_000.0), all);
+            assert_eq!(visvalingam(&points, 0), all);
+        }
+    }
+
+    #[test]
+    fn collinear_points_collapse_to_the_endpoints() {
+        let points = line(50, 0.0);
+
+        assert_eq!(douglas_peucker(&points, 1.0), vec![0, 49]);
+        assert_eq!(visvalingam(&points, 2), vec![0, 49]);
+    }
+
+    #[test]
+    fn douglas_peucker_keeps_endpoints_and_deviations() {
+        let points = zigzag(21);
+
+        assert_eq!(douglas_peucker(&points, 1.0), (0..21).collect::<Vec<_>>());
+
+        let kept = douglas_peucker(&points, 500.0);
+        assert_eq!(kept, vec![0, 20]);
+    }
+
+    #[test]
+    fn douglas_peucker_keeps_a_corner() {
+        let mut points = line(10, 0.0);
+        points.extend((1..10).map(|i| point(i as f64 * 0.001, 0.009)));
+
+        assert_eq!(douglas_peucker(&points, 1.0), vec![0, 9, 18]);
+    }
+
+    #[test]
+    fn visvalingam_respects_max_points() {
+        let points = zigzag(100);
+
+        for max_points in [2, 3, 10, 99] {
+            let kept = visvalingam(&points, max_points);
+
+            assert_eq!(kept.len(), max_points);
+            assert_eq!(kept.first(), Some(&0));
+            assert_eq!(kept.last(), Some(&99));
+            assert!(kept.windows(2).all(|w| w[0] < w[1]));
+        }
+
+        // INFO: Fewer than two points would lose an endpoint
+        assert_eq!(visvalingam(&points, 0), vec![0, 99]);
+        assert_eq!(visvalingam(&points, 100).len(), 100);
+    }
+
+    #[test]
+    fn projection_crosses_the_antimeridian() {
+        let points = [point(0.0, 179.999), point(0.0, -179.999)];
+        let xy = project(&points);
+
+        assert!((xy[1].0 - haversine(points[0], points[1])).abs() < 0.01);
+        assert_eq!(xy[1].1, 0.0);
+    }
+
+    #[test]
+    fn straight_track_over_the_antimeridian_collapses() {
+        let points: Vec<_> = line(11, 179.995)
+            .into_iter()
+            .map(|p| point(p.lat, if p.lon > 180.0 { p.lon - 360.0 } else { p.lon }))
+            .collect();
+
+        assert_eq!(douglas_peucker(&points, 1.0), vec![0, 10]);
+        assert_eq!(visvalingam(&points, 2), vec![0, 10]);
+    }
+
+    #[test]
+    fn contains_across_the_antimeridian() {
+        let polygon = [
+            point(-1.0, 179.0),
+            point(-1.0, -179.0),
+            point(1.0, -179.0),
+            point(1.0, 179.0),
+        ];
+
+        assert!(contains(&polygon, point(0.0, 180.0)));
+        assert!(contains(&polygon, point(0.0, -179.5)));
+        assert!(!contains(&polygon, point(0.0, 0.0)));
+    }
+}
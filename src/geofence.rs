@@ -0,0 +1,128 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseTransaction, EntityTrait, JoinType,
+    QueryFilter, QuerySelect, RelationTrait,
+};
+
+use crate::{
+    Result,
+    entity::{
+        geofence_events, geofence_trackers, geofences,
+        prelude::Geofences,
+        sea_orm_active_enums::{Shape, Transition},
+    },
+    geo::{self, Point},
+    ingest::Leg,
+};
+
+/// The area of a geofence, ready to test points against
+pub enum Area {
+    Circle { center: Point, radius_m: f64 },
+    Polygon(Vec<Point>),
+}
+
+impl Area {
+    /// `None` when the stored columns don't describe a shape
+    pub fn of(fence: &geofences::Model) -> Option<Self> {
+        match fence.shape {
+            Shape::Circle => Some(Self::Circle {
+                center: Point {
+                    lat: fence.lat?,
+                    lon: fence.lon?,
+                },
+                radius_m: fence.radius_m?,
+            }),
+            Shape::Polygon => {
+                let points = serde_json::from_value(fence.points.clone()?).ok()?;
+                Some(Self::Polygon(points))
+            }
+        }
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        match self {
+            Self::Circle { center, radius_m } => geo::haversine(*center, p) <= *radius_m,
+            Self::Polygon(points) => geo::contains(points, p),
+        }
+    }
+}
+
+/// A geofence a tracker entered or left
+pub struct Crossing {
+    pub name: String,
+    pub event: geofence_events::Model,
+}
+
+/// Records an event for every fence of the tracker that the path from `start` along `legs`
+/// enters or leaves. Without a `start` the first leg only sets where the tracker is.
+pub async fn cross(
+    txn: &DatabaseTransaction,
+    tracker_id: u64,
+    start: Option<Point>,
+    legs: &[Leg],
+) -> Result<Vec<Crossing>> {
+    if legs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let fences = Geofences::find()
+        .join(
+            JoinType::InnerJoin,
+            geofences::Relation::GeofenceTrackers.def(),
+        )
+        .filter(geofence_trackers::Column::TrackerId.eq(tracker_id))
+        .all(txn)
+        .await?;
+
+    let mut crossings = Vec::new();
+    let mut models = Vec::new();
+
+    for fence in fences {
+        let Some(area) = Area::of(&fence) else {
+            continue;
+        };
+
+        let mut inside = start.map(|start| area.contains(start));
+
+        for leg in legs {
+            let now = area.contains(leg.point);
+
+            let transition = match (inside, now) {
+                (Some(false), true) => Some(Transition::Enter),
+                (Some(true), false) => Some(Transition::Exit),
+                _ => None,
+            };
+
+            if let Some(transition) = transition {
+                models.push(geofence_events::ActiveModel {
+                    geofence_id: Set(fence.id),
+                    tracker_id: Set(tracker_id),
+                    ping_id: Set(leg.id),
+                    transition: Set(transition),
+                    recorded_at: Set(leg.recorded_at),
+                    created_at: Set(Utc::now()),
+
+                    ..Default::default()
+                });
+                crossings.push(fence.name.clone());
+            }
+
+            inside = Some(now);
+        }
+    }
+
+    if models.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // INFO: Same as pings, one row per statement so every event gets its real id
+    let mut events = Vec::with_capacity(models.len());
+    for (model, name) in models.into_iter().zip(crossings) {
+        events.push(Crossing {
+            name,
+            event: model.insert(txn).await?,
+        });
+    }
+
+    Ok(events)
+}
@@ -4,3 +4,6 @@ pub mod root;
 pub mod v1;
 
 pub const DEFAULT_PORT: u16 = 3000;
+
+/// Where the SPA is served from during local development
+pub const DEV_ORIGIN: &str = "http://localhost:42069";
@@ -1,4 +1,5 @@
-use serde::{Deserialize, Deserializer};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Deserializer, de::Error};
 
 pub fn deserialize_trim<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
@@ -9,6 +10,30 @@ where
     Ok(trimmed.and_then(|t| if t.is_empty() { None } else { Some(t) }))
 }
 
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Timestamp {
+    Millis(i64),
+    Text(String),
+}
+
+pub fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let timestamp = match Option::<Timestamp>::deserialize(deserializer)? {
+        Some(Timestamp::Millis(millis)) => DateTime::from_timestamp_millis(millis),
+        Some(Timestamp::Text(text)) => DateTime::parse_from_rfc3339(text.trim())
+            .ok()
+            .map(|dt| dt.to_utc()),
+        None => return Ok(None),
+    };
+
+    timestamp
+        .map(Some)
+        .ok_or_else(|| D::Error::custom("expected an ISO-8601 date or epoch milliseconds"))
+}
+
 #[derive(Deserialize)]
 pub struct QueryParams {
     pub skip: Option<u64>,
@@ -19,3 +44,171 @@ pub struct QueryParams {
     #[serde(default, deserialize_with = "deserialize_trim")]
     pub q: Option<String>,
 }
+
+/// A time window of `from`/`to` RFC 3339 timestamps, or `since` a duration like `24h` ago
+#[derive(Debug, Default, Deserialize)]
+pub struct RangeParams {
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    pub from: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    pub to: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    pub since: Option<String>,
+}
+
+/// Inclusive bounds of a validated [`RangeParams`]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Range {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl Range {
+    /// Closes the range, ending now and spanning `default` unless told otherwise, and refuses
+    /// spans longer than `max_days`
+    pub fn bounded(
+        &self,
+        default: Duration,
+        max_days: i64,
+    ) -> crate::Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let to = self.to.unwrap_or_else(Utc::now);
+        let from = self.from.unwrap_or(to - default);
+
+        if to - from > Duration::days(max_days) {
+            return Err(crate::Error::BadRequest(format!(
+                "Range must not span more than {max_days} days"
+            )));
+        }
+
+        Ok((from, to))
+    }
+}
+
+fn rfc3339(name: &str, value: &str) -> crate::Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.to_utc())
+        .map_err(|_| {
+            crate::Error::BadRequest(format!(
+                "{name} must be an RFC 3339 timestamp like 2026-01-31T12:00:00Z"
+            ))
+        })
+}
+
+/// Parses durations like `90s`, `15m`, `24h`, `7d` or `2w`
+fn duration(value: &str) -> Option<Duration> {
+    let (index, _) = value.char_indices().next_back()?;
+    let (amount, unit) = value.split_at(index);
+    let amount = amount.parse::<i64>().ok().filter(|amount| *amount > 0)?;
+
+    match unit {
+        "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    }
+}
+
+impl RangeParams {
+    pub fn range(&self) -> crate::Result<Range> {
+        let from = match (&self.from, &self.since) {
+            (Some(_), Some(_)) => {
+                return Err(crate::Error::BadRequest(
+                    "since can't be combined with from".into(),
+                ));
+            }
+            (Some(from), None) => Some(rfc3339("from", from)?),
+            (None, Some(since)) => {
+                let from = duration(since)
+                    .and_then(|duration| Utc::now().checked_sub_signed(duration))
+                    .ok_or(crate::Error::BadRequest(
+                        "since must be a duration like 30m, 24h or 7d".into(),
+                    ))?;
+
+                Some(from)
+            }
+            (None, None) => None,
+        };
+
+        let to = self.to.as_deref().map(|to| rfc3339("to", to)).transpose()?;
+
+        if let (Some(from), Some(to)) = (from, to)
+            && from > to
+        {
+            return Err(crate::Error::BadRequest("from must not be after to".into()));
+        }
+
+        Ok(Range { from, to })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn since(value: &str) -> crate::Result<Range> {
+        RangeParams {
+            from: None,
+            to: None,
+            since: Some(value.into()),
+        }
+        .range()
+    }
+
+    #[test]
+    fn bounds_ranges() {
+        let to = Utc::now();
+        let range = Range {
+            from: Some(to - Duration::days(366)),
+            to: Some(to),
+        };
+        assert!(range.bounded(Duration::days(1), 366).is_ok());
+
+        let range = Range {
+            from: Some(to - Duration::days(367)),
+            to: Some(to),
+        };
+        assert!(matches!(
+            range.bounded(Duration::days(1), 366),
+            Err(crate::Error::BadRequest(_))
+        ));
+
+        let range = Range {
+            from: None,
+            to: Some(to),
+        };
+        assert_eq!(
+            range.bounded(Duration::days(30), 366).ok(),
+            Some((to - Duration::days(30), to))
+        );
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(duration("90s"), Some(Duration::seconds(90)));
+        assert_eq!(duration("15m"), Some(Duration::minutes(15)));
+        assert_eq!(duration("24h"), Some(Duration::hours(24)));
+        assert_eq!(duration("7d"), Some(Duration::days(7)));
+        assert_eq!(duration("2w"), Some(Duration::weeks(2)));
+    }
+
+    #[test]
+    fn rejects_malformed_durations() {
+        for value in ["", "s", "0s", "-5m", "5", "5y", "1.5h", "1é", "é", "5mé"] {
+            assert_eq!(duration(value), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn reports_bad_since_as_bad_request() {
+        for value in ["1é", "9223372036854775807s", "100000000000d"] {
+            assert!(
+                matches!(since(value), Err(crate::Error::BadRequest(_))),
+                "{value}"
+            );
+        }
+
+        assert!(since("24h").unwrap().from.is_some());
+    }
+}
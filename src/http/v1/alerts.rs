@@ -0,0 +1,123 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ColumnTrait, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Select,
+};
+use serde::Serialize;
+
+use crate::{
+    AppState, Error, Result,
+    auth::AuthClaim,
+    entity::{
+        alerts,
+        prelude::{Alerts, Rules, Trackers},
+        rules, trackers,
+    },
+    http::params::{QueryParams, RangeParams},
+    skippy,
+};
+
+#[derive(Serialize, FromQueryResult)]
+struct Dto {
+    id: u64,
+    tracker_id: u64,
+    rule_id: Option<u64>,
+    kind: String,
+    message: String,
+    triggered_at: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/alerts", get(index))
+        .route("/trackers/{id}/alerts", get(tracker))
+        .route("/trackers/{id}/rules/{rule_id}/alerts", get(rule))
+}
+
+/// Alerts triggered in the range, newest first unless `desc=false`
+async fn list(
+    state: &AppState,
+    mut query: Select<Alerts>,
+    params: &QueryParams,
+    range: &RangeParams,
+) -> Result<Vec<Dto>> {
+    let (skip, take) = skippy::skip(params.skip, params.take);
+    let ord = skippy::order(params.desc, true);
+    let range = range.range()?;
+
+    if let Some(from) = range.from {
+        query = query.filter(alerts::Column::TriggeredAt.gte(from));
+    }
+
+    if let Some(to) = range.to {
+        query = query.filter(alerts::Column::TriggeredAt.lte(to));
+    }
+
+    let alerts = query
+        .offset(skip)
+        .limit(take)
+        .order_by(alerts::Column::TriggeredAt, ord.clone())
+        .order_by(alerts::Column::Id, ord)
+        .into_model::<Dto>()
+        .all(&state.db)
+        .await?;
+
+    Ok(alerts)
+}
+
+async fn index(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Query(params): Query<QueryParams>,
+    Query(range): Query<RangeParams>,
+) -> Result<Json<Vec<Dto>>> {
+    let query = Alerts::find()
+        .inner_join(Trackers)
+        .filter(trackers::Column::UserId.eq(auth.user_id));
+
+    Ok(Json(list(&state, query, &params, &range).await?))
+}
+
+async fn tracker(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(params): Query<QueryParams>,
+    Query(range): Query<RangeParams>,
+) -> Result<Json<Vec<Dto>>> {
+    let tracker = Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let query = Alerts::find().filter(alerts::Column::TrackerId.eq(tracker.id));
+
+    Ok(Json(list(&state, query, &params, &range).await?))
+}
+
+/// The alerts a rule raised
+async fn rule(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path((id, rule_id)): Path<(u64, u64)>,
+    Query(params): Query<QueryParams>,
+    Query(range): Query<RangeParams>,
+) -> Result<Json<Vec<Dto>>> {
+    let rule = Rules::find_by_id(rule_id)
+        .inner_join(Trackers)
+        .filter(rules::Column::TrackerId.eq(id))
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let query = Alerts::find().filter(alerts::Column::RuleId.eq(rule.id));
+
+    Ok(Json(list(&state, query, &params, &range).await?))
+}
@@ -0,0 +1,122 @@
+use axum::{
+    Extension, Router,
+    extract::{Path, Query, State},
+    http::{HeaderMap, header},
+    response::IntoResponse,
+    routing::get,
+};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Select};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    AppState, Error, Result,
+    auth::AuthClaim,
+    entity::{
+        pings,
+        prelude::{Pings, Trackers},
+        trackers,
+    },
+    export::{self, Format},
+    http::{
+        params::{QueryParams, deserialize_trim},
+        v1::pings::{FilterParams, query},
+    },
+    util,
+};
+
+#[derive(Debug, Deserialize)]
+struct FormatParams {
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    format: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/pings/export", get(index))
+        .route("/trackers/{id}/export.gpx", get(gpx))
+}
+
+async fn download(
+    state: &AppState,
+    filters: &FilterParams,
+    query: Select<Pings>,
+    format: Format,
+    names: HashMap<u64, String>,
+    name: &str,
+) -> Result<axum::response::Response> {
+    let headers = [
+        (header::CONTENT_TYPE, format.content_type().to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!(r#"attachment; filename="{name}.{}""#, format.extension()),
+        ),
+    ];
+
+    let query = query
+        .order_by_asc(pings::Column::TrackerId)
+        .order_by_asc(pings::Column::RecordedAt)
+        .order_by_asc(pings::Column::Id);
+
+    let keep = filters
+        .reduce(&state.db, query.clone())
+        .await?
+        .map(HashSet::from_iter);
+
+    let body = export::stream(state.db.clone(), query, keep, format.writer(names));
+
+    Ok((headers, body).into_response())
+}
+
+/// Exports the caller's pings matching the listing filters, `?format=` wins over `Accept`
+async fn index(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<QueryParams>,
+    Query(filters): Query<FilterParams>,
+    Query(format): Query<FormatParams>,
+) -> Result<impl IntoResponse> {
+    let format = match format.format {
+        Some(format) => format
+            .parse::<Format>()
+            .map_err(|_| Error::BadRequest("Unsupported format".into()))?,
+        None => headers
+            .get(header::ACCEPT)
+            .and_then(|header| header.to_str().ok())
+            .and_then(Format::from_accept)
+            .unwrap_or(Format::GeoJson),
+    };
+
+    let names = Trackers::find()
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|tracker| (tracker.id, tracker.name))
+        .collect();
+
+    let query = query(auth.user_id, &params, &filters)?;
+
+    download(&state, &filters, query, format, names, "pings").await
+}
+
+async fn gpx(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(filters): Query<FilterParams>,
+) -> Result<impl IntoResponse> {
+    let tracker = Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let query = filters.apply(Pings::find().filter(pings::Column::TrackerId.eq(tracker.id)))?;
+
+    let slug = util::sqids()?.encode(&[tracker.id])?;
+    let names = HashMap::from([(tracker.id, tracker.name)]);
+
+    download(&state, &filters, query, Format::Gpx, names, &slug).await
+}
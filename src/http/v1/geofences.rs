@@ -0,0 +1,400 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    routing::{delete, get, post, put},
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, Condition, DatabaseTransaction, EntityTrait,
+    FromQueryResult, IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+    TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use validator::Validate;
+
+use crate::{
+    AppState, Error, Response, Result,
+    auth::AuthClaim,
+    entity::{
+        geofence_events, geofence_trackers, geofences, pings,
+        prelude::{GeofenceEvents, GeofenceTrackers, Geofences, Pings, Trackers},
+        sea_orm_active_enums::{Shape, Transition},
+        trackers,
+    },
+    geo::{self, Point},
+    http::params::{QueryParams, RangeParams},
+    skippy,
+};
+
+#[derive(Serialize)]
+struct Dto {
+    id: u64,
+    name: String,
+    shape: Shape,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    radius_m: Option<f64>,
+    points: Option<serde_json::Value>,
+    tracker_ids: Vec<u64>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, FromQueryResult)]
+struct EventDto {
+    id: u64,
+    geofence_id: u64,
+    geofence_name: String,
+    tracker_id: u64,
+    ping_id: u64,
+    transition: Transition,
+    lat: f64,
+    lon: f64,
+    recorded_at: DateTime<Utc>,
+}
+
+/// A circle of `radius_m` meters around `lat`,`lon`, or a polygon of at least three `points`
+#[derive(Debug, Deserialize, Validate)]
+struct GeofenceParams {
+    #[validate(length(min = 1))]
+    name: String,
+    shape: Shape,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    radius_m: Option<f64>,
+    points: Option<Vec<Point>>,
+    #[serde(default)]
+    tracker_ids: Vec<u64>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/geofences", get(index))
+        .route("/geofences", post(store))
+        .route("/geofences/count", get(count))
+        .route("/geofences/{id}", get(show))
+        .route("/geofences/{id}", put(update))
+        .route("/geofences/{id}", delete(destroy))
+        .route("/geofences/{id}/events", get(events))
+        .route("/trackers/{id}/geofence-events", get(tracker_events))
+}
+
+impl GeofenceParams {
+    /// Validates the shape into `fence`, clearing the columns of the other shape
+    fn apply(&self, fence: &mut geofences::ActiveModel) -> Result<()> {
+        let (lat, lon, radius_m, points) = match self.shape {
+            Shape::Circle => {
+                let (Some(lat), Some(lon)) = (self.lat, self.lon) else {
+                    return Err(Error::BadRequest("A circle requires lat and lon".into()));
+                };
+
+                if !geo::valid(lat, lon) {
+                    return Err(Error::BadRequest("lat,lon is not a coordinate".into()));
+                }
+
+                let radius_m = self
+                    .radius_m
+                    .filter(|radius_m| radius_m.is_finite() && *radius_m > 0.0)
+                    .ok_or(Error::BadRequest("radius_m must be positive".into()))?;
+
+                (Some(lat), Some(lon), Some(radius_m), None)
+            }
+            Shape::Polygon => {
+                let points = self.points.as_deref().unwrap_or_default();
+
+                if points.len() < 3 {
+                    return Err(Error::BadRequest(
+                        "A polygon requires at least 3 points".into(),
+                    ));
+                }
+
+                if points.iter().any(|point| !geo::valid(point.lat, point.lon)) {
+                    return Err(Error::BadRequest(
+                        "Every point must be a lat,lon coordinate".into(),
+                    ));
+                }
+
+                let points =
+                    serde_json::to_value(points).map_err(|err| Error::Internal(err.to_string()))?;
+
+                (None, None, None, Some(points))
+            }
+        };
+
+        fence.name = Set(self.name.clone());
+        fence.shape = Set(self.shape.clone());
+        fence.lat = Set(lat);
+        fence.lon = Set(lon);
+        fence.radius_m = Set(radius_m);
+        fence.points = Set(points);
+
+        Ok(())
+    }
+}
+
+fn query(user_id: u64, params: &QueryParams) -> Select<Geofences> {
+    let q = params.q.clone().unwrap_or_default();
+    let query = Geofences::find().filter(geofences::Column::UserId.eq(user_id));
+
+    if q.is_empty() {
+        return query;
+    }
+
+    query.filter(
+        Condition::any()
+            .add(geofences::Column::Id.eq(&q))
+            .add(geofences::Column::Name.contains(&q)),
+    )
+}
+
+async fn find(state: &AppState, user_id: u64, id: u64) -> Result<geofences::Model> {
+    Geofences::find_by_id(id)
+        .filter(geofences::Column::UserId.eq(user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+/// Attaches the fences to the trackers they apply to
+async fn dtos(state: &AppState, fences: Vec<geofences::Model>) -> Result<Vec<Dto>> {
+    let mut tracker_ids: HashMap<u64, Vec<u64>> = HashMap::new();
+
+    for row in GeofenceTrackers::find()
+        .filter(geofence_trackers::Column::GeofenceId.is_in(fences.iter().map(|fence| fence.id)))
+        .order_by_asc(geofence_trackers::Column::TrackerId)
+        .all(&state.db)
+        .await?
+    {
+        tracker_ids
+            .entry(row.geofence_id)
+            .or_default()
+            .push(row.tracker_id);
+    }
+
+    Ok(fences
+        .into_iter()
+        .map(|fence| Dto {
+            tracker_ids: tracker_ids.remove(&fence.id).unwrap_or_default(),
+            id: fence.id,
+            name: fence.name,
+            shape: fence.shape,
+            lat: fence.lat,
+            lon: fence.lon,
+            radius_m: fence.radius_m,
+            points: fence.points,
+            created_at: fence.created_at,
+            updated_at: fence.updated_at,
+        })
+        .collect())
+}
+
+/// Replaces the trackers of the fence, all of which have to be the user's
+async fn attach(
+    txn: &DatabaseTransaction,
+    user_id: u64,
+    geofence_id: u64,
+    tracker_ids: &[u64],
+) -> Result<()> {
+    let tracker_ids: HashSet<_> = tracker_ids.iter().copied().collect();
+
+    let owned = Trackers::find()
+        .filter(trackers::Column::Id.is_in(tracker_ids.iter().copied()))
+        .filter(trackers::Column::UserId.eq(user_id))
+        .count(txn)
+        .await?;
+
+    if owned != tracker_ids.len() as u64 {
+        return Err(Error::NotFound);
+    }
+
+    GeofenceTrackers::delete_many()
+        .filter(geofence_trackers::Column::GeofenceId.eq(geofence_id))
+        .exec(txn)
+        .await?;
+
+    if tracker_ids.is_empty() {
+        return Ok(());
+    }
+
+    GeofenceTrackers::insert_many(tracker_ids.into_iter().map(|tracker_id| {
+        geofence_trackers::ActiveModel {
+            geofence_id: Set(geofence_id),
+            tracker_id: Set(tracker_id),
+        }
+    }))
+    .exec(txn)
+    .await?;
+
+    Ok(())
+}
+
+async fn index(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Query(params): Query<QueryParams>,
+) -> Result<Json<Vec<Dto>>> {
+    let (skip, take) = skippy::skip(params.skip, params.take);
+    let col = skippy::column(params.sort.clone(), geofences::Column::UpdatedAt);
+    let ord = skippy::order(params.desc, true);
+
+    let fences = query(auth.user_id, &params)
+        .offset(skip)
+        .limit(take)
+        .order_by(col, ord)
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(dtos(&state, fences).await?))
+}
+
+async fn count(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Query(params): Query<QueryParams>,
+) -> Result<Json<u64>> {
+    let count = query(auth.user_id, &params).count(&state.db).await?;
+
+    Ok(Json(count))
+}
+
+async fn store(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Json(params): Json<GeofenceParams>,
+) -> Result<Json<u64>> {
+    if let Err(err) = params.validate() {
+        return Err(Error::BadRequest(err.to_string()));
+    }
+
+    let mut fence = geofences::ActiveModel {
+        user_id: Set(auth.user_id),
+
+        ..Default::default()
+    };
+    params.apply(&mut fence)?;
+
+    let txn = state.db.begin().await?;
+    let fence = fence.insert(&txn).await?;
+
+    attach(&txn, auth.user_id, fence.id, &params.tracker_ids).await?;
+    txn.commit().await?;
+
+    Ok(Json(fence.id))
+}
+
+async fn show(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Dto>> {
+    let fence = find(&state, auth.user_id, id).await?;
+    let mut dtos = dtos(&state, vec![fence]).await?;
+
+    dtos.pop().map(Json).ok_or(Error::NotFound)
+}
+
+async fn update(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Json(params): Json<GeofenceParams>,
+) -> Result<Response> {
+    if let Err(err) = params.validate() {
+        return Err(Error::BadRequest(err.to_string()));
+    }
+
+    let mut fence = find(&state, auth.user_id, id).await?.into_active_model();
+    params.apply(&mut fence)?;
+    fence.updated_at = Set(Utc::now());
+
+    let txn = state.db.begin().await?;
+    let fence = fence.update(&txn).await?;
+
+    attach(&txn, auth.user_id, fence.id, &params.tracker_ids).await?;
+    txn.commit().await?;
+
+    Ok(Response::Accepted)
+}
+
+async fn destroy(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Response> {
+    let fence = find(&state, auth.user_id, id).await?;
+
+    Geofences::delete_by_id(fence.id).exec(&state.db).await?;
+
+    Ok(Response::NoContent)
+}
+
+/// Enter and exit events, newest first unless `desc=false`
+async fn list(
+    state: &AppState,
+    query: Select<GeofenceEvents>,
+    params: &QueryParams,
+    range: &RangeParams,
+) -> Result<Vec<EventDto>> {
+    let (skip, take) = skippy::skip(params.skip, params.take);
+    let ord = skippy::order(params.desc, true);
+    let range = range.range()?;
+
+    let mut query = query
+        .inner_join(Geofences)
+        .inner_join(Pings)
+        .column_as(geofences::Column::Name, "geofence_name")
+        .column_as(pings::Column::Lat, "lat")
+        .column_as(pings::Column::Lon, "lon");
+
+    if let Some(from) = range.from {
+        query = query.filter(geofence_events::Column::RecordedAt.gte(from));
+    }
+
+    if let Some(to) = range.to {
+        query = query.filter(geofence_events::Column::RecordedAt.lte(to));
+    }
+
+    let events = query
+        .offset(skip)
+        .limit(take)
+        .order_by(geofence_events::Column::RecordedAt, ord.clone())
+        .order_by(geofence_events::Column::Id, ord)
+        .into_model::<EventDto>()
+        .all(&state.db)
+        .await?;
+
+    Ok(events)
+}
+
+async fn events(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(params): Query<QueryParams>,
+    Query(range): Query<RangeParams>,
+) -> Result<Json<Vec<EventDto>>> {
+    let fence = find(&state, auth.user_id, id).await?;
+
+    let query = GeofenceEvents::find().filter(geofence_events::Column::GeofenceId.eq(fence.id));
+
+    Ok(Json(list(&state, query, &params, &range).await?))
+}
+
+async fn tracker_events(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(params): Query<QueryParams>,
+    Query(range): Query<RangeParams>,
+) -> Result<Json<Vec<EventDto>>> {
+    let tracker = Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let query = GeofenceEvents::find().filter(geofence_events::Column::TrackerId.eq(tracker.id));
+
+    Ok(Json(list(&state, query, &params, &range).await?))
+}
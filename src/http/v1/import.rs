@@ -0,0 +1,201 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{DefaultBodyLimit, Multipart, Path, Query, State, multipart::Field},
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, FromQueryResult, QueryFilter,
+    QueryOrder, QuerySelect,
+};
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    AppState, Error, Response, Result,
+    auth::AuthClaim,
+    entity::{
+        import_jobs,
+        prelude::{ImportJobs, Trackers},
+        sea_orm_active_enums::Status,
+        trackers,
+    },
+    http::params::QueryParams,
+    import, ingest, skippy,
+};
+
+/// Track files of a few years of pings at one per second stay well under this
+const MAX_FILE_SIZE: usize = 64 * 1024 * 1024;
+
+/// Takeout location histories of heavy users run into the hundreds of megabytes
+const MAX_TAKEOUT_SIZE: usize = 1024 * 1024 * 1024;
+
+#[derive(Serialize, FromQueryResult)]
+struct JobDto {
+    id: u64,
+    tracker_id: u64,
+    filename: String,
+    status: Status,
+    total_bytes: u64,
+    processed_bytes: u64,
+    created_count: u64,
+    skipped_count: u64,
+    error: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/trackers/{id}/import",
+            post(store).layer(DefaultBodyLimit::max(MAX_FILE_SIZE)),
+        )
+        .route(
+            "/trackers/{id}/import/takeout",
+            post(takeout).layer(DefaultBodyLimit::max(MAX_TAKEOUT_SIZE)),
+        )
+        .route("/imports", get(index))
+        .route("/imports/{id}", get(show))
+}
+
+async fn tracker(state: &AppState, id: u64, user_id: u64) -> Result<trackers::Model> {
+    Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+async fn store(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    mut multipart: Multipart,
+) -> Result<Response> {
+    let tracker = tracker(&state, id, auth.user_id).await?;
+
+    let mut fixes = None;
+    while let Some(field) = multipart.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let filename = field.file_name().map(str::to_string);
+        let bytes = field.bytes().await?;
+        fixes = Some(import::parse(filename.as_deref(), &bytes).map_err(Error::BadRequest)?);
+        break;
+    }
+
+    let fixes = fixes.ok_or(Error::BadRequest("Missing file".into()))?;
+    let (created, skipped) = ingest::import(&state.db, &tracker, fixes).await?;
+
+    if !created.is_empty() {
+        ingest::odometer(&state.db, tracker.id).await?;
+    }
+
+    let skipped = skipped
+        .into_iter()
+        .map(|(index, err)| format!("{index}: {err}"))
+        .collect();
+
+    Ok(Response::CreatedBatch(created, skipped))
+}
+
+async fn spool(field: &mut Field<'_>, path: &std::path::Path) -> Result<u64> {
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut size = 0;
+
+    while let Some(chunk) = field.chunk().await? {
+        size += chunk.len() as u64;
+        file.write_all(&chunk).await?;
+    }
+
+    file.flush().await?;
+
+    Ok(size)
+}
+
+async fn takeout(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    mut multipart: Multipart,
+) -> Result<Response> {
+    let tracker = tracker(&state, id, auth.user_id).await?;
+
+    // INFO: The upload is spooled to disk so the job can stream it without holding it in memory
+    let path = std::env::temp_dir().join(format!("dracker-takeout-{}.json", uuid::Uuid::new_v4()));
+    let mut upload = None;
+
+    while let Some(mut field) = multipart.next_field().await? {
+        if field.name() != Some("file") {
+            continue;
+        }
+
+        let filename = field.file_name().unwrap_or("Records.json").to_string();
+        let size = match spool(&mut field, &path).await {
+            Ok(size) => size,
+            Err(err) => {
+                let _ = tokio::fs::remove_file(&path).await;
+                return Err(err);
+            }
+        };
+
+        upload = Some((filename, size));
+        break;
+    }
+
+    let (filename, size) = upload.ok_or(Error::BadRequest("Missing file".into()))?;
+
+    let job = import_jobs::ActiveModel {
+        user_id: Set(auth.user_id),
+        tracker_id: Set(tracker.id),
+        filename: Set(filename),
+        status: Set(Status::Pending),
+        total_bytes: Set(size),
+
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await?;
+
+    let id = job.id;
+    tokio::spawn(import::takeout::run(state.db.clone(), job, tracker, path));
+
+    Ok(Response::Created(id))
+}
+
+async fn index(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Query(params): Query<QueryParams>,
+) -> Result<Json<Vec<JobDto>>> {
+    let (skip, take) = skippy::skip(params.skip, params.take);
+
+    let jobs = ImportJobs::find()
+        .filter(import_jobs::Column::UserId.eq(auth.user_id))
+        .offset(skip)
+        .limit(take)
+        .order_by_desc(import_jobs::Column::Id)
+        .into_model::<JobDto>()
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(jobs))
+}
+
+async fn show(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<JobDto>> {
+    let job = ImportJobs::find_by_id(id)
+        .filter(import_jobs::Column::UserId.eq(auth.user_id))
+        .into_model::<JobDto>()
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(job))
+}
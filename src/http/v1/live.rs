@@ -0,0 +1,121 @@
+use axum::{
+    Extension, Router,
+    extract::{Path, Query, State},
+    response::sse::{Event as SseEvent, KeepAlive, Sse},
+    routing::get,
+};
+use futures::{Stream, StreamExt, future};
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde::Deserialize;
+use std::{collections::HashSet, time::Duration};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    AppState, Error, Result,
+    auth::AuthClaim,
+    entity::{prelude::Trackers, trackers},
+    events::Event,
+    http::params::deserialize_trim,
+};
+
+/// Seconds between the comments that keep idle connections and proxies from timing out
+const HEARTBEAT_SECONDS: u64 = 15;
+
+#[derive(Debug, Deserialize)]
+struct LiveParams {
+    /// Comma separated tracker ids, all of the caller's trackers when absent
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    ids: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/trackers/live", get(index))
+        .route("/trackers/{id}/live", get(show))
+}
+
+/// Streams the events `keep` lets through until the client goes away. A client too slow to
+/// keep up skips what it missed rather than holding everyone else back.
+fn stream<F>(
+    rx: broadcast::Receiver<Event>,
+    keep: F,
+) -> Sse<impl Stream<Item = std::result::Result<SseEvent, axum::Error>>>
+where
+    F: Fn(&Event) -> bool + Send + 'static,
+{
+    let events = futures::stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => return Some((event, rx)),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .filter(move |event| future::ready(keep(event)))
+    .map(|event| SseEvent::default().event(event.name()).json_data(&event));
+
+    Sse::new(events).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(HEARTBEAT_SECONDS))
+            .text("heartbeat"),
+    )
+}
+
+async fn index(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Query(params): Query<LiveParams>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<SseEvent, axum::Error>>>> {
+    // INFO: Subscribe first so nothing published during the ownership check is lost
+    let rx = state.events.subscribe();
+    let user_id = auth.user_id;
+
+    let ids = match params.ids {
+        Some(ids) => {
+            let ids = ids
+                .split(',')
+                .map(|id| id.trim().parse::<u64>())
+                .collect::<std::result::Result<HashSet<_>, _>>()
+                .map_err(|_| Error::BadRequest("ids must be comma separated tracker ids".into()))?;
+
+            let owned = Trackers::find()
+                .filter(trackers::Column::Id.is_in(ids.iter().copied()))
+                .filter(trackers::Column::UserId.eq(user_id))
+                .count(&state.db)
+                .await?;
+
+            if owned != ids.len() as u64 {
+                return Err(Error::NotFound);
+            }
+
+            Some(ids)
+        }
+        None => None,
+    };
+
+    Ok(stream(rx, move |event: &Event| {
+        event.user_id() == user_id
+            && ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&event.tracker_id()))
+    }))
+}
+
+async fn show(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<SseEvent, axum::Error>>>> {
+    let rx = state.events.subscribe();
+
+    let tracker = Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(stream(rx, move |event: &Event| {
+        event.tracker_id() == tracker.id
+    }))
+}
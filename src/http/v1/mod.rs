@@ -2,29 +2,61 @@ use axum::{Router, middleware};
 
 use crate::{AppState, http::middleware::auth};
 
+pub mod alerts;
 pub mod auth;
+pub mod export;
+pub mod geofences;
+pub mod import;
+pub mod live;
+pub mod osmand;
+pub mod overland;
+pub mod owntracks;
 pub mod password;
 pub mod ping;
 pub mod pings;
+pub mod rules;
+pub mod secrets;
+pub mod share;
+pub mod shares;
 pub mod signup;
+pub mod stats;
 pub mod tokens;
 pub mod trackers;
+pub mod trips;
 pub mod users;
+pub mod webhooks;
+pub mod ws;
 
 pub fn routes(state: &AppState) -> Router<AppState> {
     // INFO: PUBLIC ROUTES
     let publ_router = Router::new()
         .merge(auth::routes(state))
+        .merge(osmand::routes())
+        .merge(overland::routes())
+        .merge(owntracks::routes())
         .merge(password::routes())
         .merge(ping::routes())
+        .merge(share::routes())
         .merge(signup::routes());
 
     // WARN: AUTHENTICATED ROUTES
     let auth_router = Router::new()
+        .merge(alerts::routes())
+        .merge(export::routes())
+        .merge(geofences::routes())
+        .merge(import::routes())
+        .merge(live::routes())
         .merge(pings::routes())
+        .merge(rules::routes())
+        .merge(secrets::routes())
+        .merge(shares::routes())
+        .merge(stats::routes())
         .merge(tokens::routes())
         .merge(trackers::routes())
+        .merge(trips::routes())
         .merge(users::routes())
+        .merge(webhooks::routes())
+        .merge(ws::routes())
         .layer(middleware::from_fn_with_state(state.clone(), auth));
 
     Router::new().nest("/v1", Router::new().merge(publ_router).merge(auth_router))
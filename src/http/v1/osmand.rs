@@ -0,0 +1,88 @@
+use axum::{
+    Router,
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::{
+    Error, Result,
+    http::{
+        params::deserialize_trim,
+        v1::ping::{secret, tracker},
+    },
+    ingest::{self, Fix},
+    state::AppState,
+};
+
+const MPS_PER_KNOT: f64 = 0.514_444;
+
+/// A fix as sent by OsmAnd and the Traccar Client app, speed is in knots.
+#[derive(Debug, Deserialize)]
+struct OsmAndParams {
+    id: String,
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    secret: Option<String>,
+    lat: f64,
+    lon: f64,
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    timestamp: Option<String>,
+    speed: Option<f64>,
+    #[serde(alias = "heading")]
+    bearing: Option<f64>,
+    altitude: Option<f64>,
+    accuracy: Option<f64>,
+    batt: Option<f64>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/osmand", get(store).post(store))
+}
+
+fn timestamp(value: &str) -> Option<DateTime<Utc>> {
+    match value.parse::<i64>() {
+        // INFO: Traccar Client sends seconds, OsmAnd sends milliseconds
+        Ok(ts) if ts > 100_000_000_000 => DateTime::from_timestamp_millis(ts),
+        Ok(ts) => DateTime::from_timestamp(ts, 0),
+        Err(_) => DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|dt| dt.to_utc()),
+    }
+}
+
+async fn store(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(params): Query<OsmAndParams>,
+) -> Result<StatusCode> {
+    let secret = params.secret.as_deref().or(secret(&headers));
+    let tracker = tracker(&state.db, &params.id, secret).await?;
+
+    let recorded_at = match params.timestamp.as_deref() {
+        Some(value) => Some(timestamp(value).ok_or(Error::BadRequest("Invalid timestamp".into()))?),
+        None => None,
+    };
+
+    let fix = Fix {
+        lat: params.lat,
+        lon: params.lon,
+        speed: params.speed.map(|knots| knots * MPS_PER_KNOT),
+        heading: params.bearing,
+        altitude: params.altitude,
+        accuracy: params.accuracy,
+        battery: params.batt,
+        recorded_at,
+
+        ..Default::default()
+    };
+
+    if let Err(err) = fix.validate(&tracker) {
+        return Err(Error::BadRequest(err));
+    }
+
+    ingest::store(&state, &tracker, vec![fix]).await?;
+
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,121 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, header},
+    routing::post,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tracing::debug;
+
+use crate::{
+    Result,
+    http::v1::ping::{secret, tracker},
+    ingest::{self, Fix},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+struct BatchParams {
+    locations: Vec<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Feature {
+    geometry: Geometry,
+    #[serde(default)]
+    properties: Properties,
+}
+
+#[derive(Debug, Deserialize)]
+struct Geometry {
+    #[serde(rename = "type")]
+    kind: String,
+    coordinates: Vec<f64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Properties {
+    timestamp: Option<String>,
+    speed: Option<f64>,
+    course: Option<f64>,
+    altitude: Option<f64>,
+    horizontal_accuracy: Option<f64>,
+    battery_level: Option<f64>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/overland/{slug}", post(store))
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header_str| header_str.strip_prefix("Bearer "))
+}
+
+fn timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        .ok()
+        .map(|dt| dt.to_utc())
+}
+
+/// iOS reports unknown readings as negative values
+fn known(value: Option<f64>) -> Option<f64> {
+    value.filter(|value| *value >= 0.0)
+}
+
+fn fix(value: Value) -> std::result::Result<Fix, String> {
+    let feature = serde_json::from_value::<Feature>(value).map_err(|err| err.to_string())?;
+
+    if feature.geometry.kind != "Point" || feature.geometry.coordinates.len() < 2 {
+        return Err("Expected a Point geometry".into());
+    }
+
+    let properties = feature.properties;
+    let recorded_at = match properties.timestamp.as_deref() {
+        Some(value) => Some(timestamp(value).ok_or("Invalid timestamp")?),
+        None => None,
+    };
+
+    Ok(Fix {
+        lat: feature.geometry.coordinates[1],
+        lon: feature.geometry.coordinates[0],
+        speed: known(properties.speed),
+        heading: known(properties.course),
+        altitude: properties.altitude,
+        accuracy: known(properties.horizontal_accuracy),
+        battery: known(properties.battery_level).map(|level| level * 100.0),
+        recorded_at,
+
+        ..Default::default()
+    })
+}
+
+async fn store(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Json(params): Json<BatchParams>,
+) -> Result<Json<Value>> {
+    let secret = bearer(&headers).or(secret(&headers));
+    let tracker = tracker(&state.db, &slug, secret).await?;
+
+    let mut fixes = Vec::with_capacity(params.locations.len());
+    for (index, value) in params.locations.into_iter().enumerate() {
+        match fix(value) {
+            Ok(fix) => fixes.push(fix),
+            Err(err) => debug!("Skipping Overland location {index}: {err}"),
+        }
+    }
+
+    let (_, skipped) = ingest::store(&state, &tracker, fixes).await?;
+    for (_, err) in skipped {
+        debug!("Skipping Overland location: {err}");
+    }
+
+    Ok(Json(json!({ "result": "ok" })))
+}
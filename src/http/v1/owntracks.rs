@@ -0,0 +1,123 @@
+use axum::{
+    Json, Router,
+    extract::State,
+    http::{HeaderMap, header},
+    routing::post,
+};
+use base64::{Engine, engine::general_purpose};
+use chrono::DateTime;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{
+    Error, Result,
+    http::v1::ping::tracker,
+    ingest::{self, Fix},
+    state::AppState,
+};
+
+const MPS_PER_KPH: f64 = 1.0 / 3.6;
+
+#[derive(Debug, Deserialize)]
+struct LocationParams {
+    lat: f64,
+    lon: f64,
+    tst: i64,
+    acc: Option<f64>,
+    alt: Option<f64>,
+    vel: Option<f64>,
+    cog: Option<f64>,
+    batt: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TransitionParams {
+    event: String,
+    #[serde(default)]
+    desc: String,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    tst: i64,
+    acc: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "_type", rename_all = "lowercase")]
+enum Message {
+    Location(LocationParams),
+    Transition(TransitionParams),
+    #[serde(other)]
+    Other,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/owntracks", post(store))
+}
+
+fn basic_auth(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header_str| header_str.strip_prefix("Basic "))?;
+
+    let decoded = general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (user, pass) = decoded.split_once(':')?;
+
+    Some((user.to_string(), pass.to_string()))
+}
+
+fn fix(message: Message) -> Option<Fix> {
+    match message {
+        Message::Location(params) => Some(Fix {
+            lat: params.lat,
+            lon: params.lon,
+            accuracy: params.acc,
+            altitude: params.alt,
+            speed: params.vel.map(|kph| kph * MPS_PER_KPH),
+            heading: params.cog,
+            battery: params.batt,
+            recorded_at: DateTime::from_timestamp(params.tst, 0),
+
+            ..Default::default()
+        }),
+        Message::Transition(params) => Some(Fix {
+            lat: params.lat?,
+            lon: params.lon?,
+            note: format!("{} {}", params.event, params.desc)
+                .trim()
+                .to_string(),
+            accuracy: params.acc,
+            recorded_at: DateTime::from_timestamp(params.tst, 0),
+
+            ..Default::default()
+        }),
+        Message::Other => None,
+    }
+}
+
+async fn store(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(message): Json<Message>,
+) -> Result<Json<Vec<Value>>> {
+    let (slug, secret) = basic_auth(&headers).ok_or(Error::Unauthorized)?;
+    let tracker = tracker(&state.db, &slug, Some(&secret)).await?;
+
+    let fix = match fix(message) {
+        Some(fix) => fix,
+        None => {
+            debug!("Ignoring OwnTracks message for tracker {}", tracker.id);
+            return Ok(Json(Vec::new()));
+        }
+    };
+
+    if let Err(err) = fix.validate(&tracker) {
+        return Err(Error::BadRequest(err));
+    }
+
+    ingest::store(&state, &tracker, vec![fix]).await?;
+
+    Ok(Json(Vec::new()))
+}
@@ -1,39 +1,153 @@
-use axum::{Json, Router, extract::State, routing::post};
-use sea_orm::{ActiveModelTrait, ActiveValue::Set};
+use axum::{Json, Router, extract::State, http::HeaderMap, routing::post};
+use chrono::{DateTime, Utc};
+use sea_orm::{DatabaseConnection, EntityTrait};
 use serde::Deserialize;
 
-use crate::{Error, Result, entity::pings, state::AppState, util};
+use crate::{
+    Error, Response, Result, auth,
+    entity::{prelude::Trackers, trackers},
+    http::params::deserialize_timestamp,
+    ingest::{self, Fix},
+    state::AppState,
+    util,
+};
+
+pub const X_TRACKER_SECRET: &str = "x-tracker-secret";
 
 #[derive(Debug, Deserialize)]
-struct PingParams {
-    slug: String,
+struct FixParams {
     lat: f64,
     lon: f64,
+    #[serde(default)]
     note: String,
+    altitude: Option<f64>,
+    speed: Option<f64>,
+    heading: Option<f64>,
+    accuracy: Option<f64>,
+    satellites: Option<u8>,
+    battery: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    recorded_at: Option<DateTime<Utc>>,
+}
+
+impl From<FixParams> for Fix {
+    fn from(params: FixParams) -> Self {
+        Fix {
+            lat: params.lat,
+            lon: params.lon,
+            note: params.note,
+            altitude: params.altitude,
+            speed: params.speed,
+            heading: params.heading,
+            accuracy: params.accuracy,
+            satellites: params.satellites,
+            battery: params.battery,
+            recorded_at: params.recorded_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PingParams {
+    slug: String,
+    secret: Option<String>,
+    #[serde(flatten)]
+    fix: FixParams,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchParams {
+    slug: String,
+    secret: Option<String>,
+    fixes: Vec<serde_json::Value>,
 }
 
 pub fn routes() -> Router<AppState> {
-    Router::new().route("/ping", post(store))
+    Router::new()
+        .route("/ping", post(store))
+        .route("/ping/batch", post(store_batch))
+}
+
+pub fn secret(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(X_TRACKER_SECRET)
+        .and_then(|header| header.to_str().ok())
 }
 
-async fn store(State(state): State<AppState>, Json(params): Json<PingParams>) -> Result<Json<u64>> {
+pub async fn tracker(
+    db: &DatabaseConnection,
+    slug: &str,
+    secret: Option<&str>,
+) -> Result<trackers::Model> {
     let sqids = util::sqids()?;
-    let tracker_id = sqids.decode(&params.slug);
+    let tracker_id = sqids.decode(slug);
     if tracker_id.is_empty() {
         return Err(Error::BadRequest("Invalid tracker_id".into()));
     }
 
-    let tracker_id = tracker_id[0];
-    let ping = pings::ActiveModel {
-        tracker_id: Set(tracker_id),
-        lat: Set(params.lat),
-        lon: Set(params.lon),
-        note: Set(params.note),
+    let tracker = Trackers::find_by_id(tracker_id[0])
+        .one(db)
+        .await?
+        .ok_or(Error::Unauthorized)?;
+
+    let hash = tracker.secret.as_deref().ok_or(Error::Unauthorized)?;
+    let secret = secret.ok_or(Error::Unauthorized)?;
+
+    if !auth::verify_password(secret, hash) {
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(tracker)
+}
+
+async fn store(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(params): Json<PingParams>,
+) -> Result<Json<u64>> {
+    let secret = params.secret.as_deref().or(secret(&headers));
+    let tracker = tracker(&state.db, &params.slug, secret).await?;
+
+    let fix = Fix::from(params.fix);
+    if let Err(err) = fix.validate(&tracker) {
+        return Err(Error::BadRequest(err));
+    }
+
+    let (created, _) = ingest::store(&state, &tracker, vec![fix]).await?;
+
+    Ok(Json(created[0]))
+}
+
+async fn store_batch(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(params): Json<BatchParams>,
+) -> Result<Response> {
+    let secret = params.secret.as_deref().or(secret(&headers));
+    let tracker = tracker(&state.db, &params.slug, secret).await?;
+
+    let mut indices = Vec::with_capacity(params.fixes.len());
+    let mut fixes = Vec::with_capacity(params.fixes.len());
+    let mut skipped = Vec::new();
 
-        ..Default::default()
+    for (index, value) in params.fixes.into_iter().enumerate() {
+        match serde_json::from_value::<FixParams>(value) {
+            Ok(fix) => {
+                indices.push(index);
+                fixes.push(Fix::from(fix));
+            }
+            Err(err) => skipped.push((index, err.to_string())),
+        }
     }
-    .insert(&state.db)
-    .await?;
 
-    Ok(Json(ping.id))
+    let (created, invalid) = ingest::store(&state, &tracker, fixes).await?;
+    skipped.extend(invalid.into_iter().map(|(i, err)| (indices[i], err)));
+    skipped.sort_by_key(|(index, _)| *index);
+
+    let skipped = skipped
+        .into_iter()
+        .map(|(index, err)| format!("{index}: {err}"))
+        .collect();
+
+    Ok(Response::CreatedBatch(created, skipped))
 }
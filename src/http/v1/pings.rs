@@ -1,17 +1,27 @@
 use axum::{
-    Json, Router,
+    Extension, Json, Router,
     extract::{Query, State},
     routing::{get, post},
 };
 use chrono::{DateTime, Utc};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue::Set, ColumnTrait, Condition, EntityTrait, FromQueryResult,
-    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, FromQueryResult, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Select, sea_query::Expr,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    Result, entity::pings, entity::prelude::Pings, http::params::QueryParams, skippy,
+    Error, Result,
+    auth::AuthClaim,
+    entity::{
+        pings,
+        prelude::{Pings, Trackers},
+        trackers,
+    },
+    geo::{self, Bbox, EARTH_RADIUS_M, Point},
+    http::params::{QueryParams, RangeParams, deserialize_timestamp, deserialize_trim},
+    ingest::{self, Fix},
+    skippy,
     state::AppState,
 };
 
@@ -22,6 +32,13 @@ struct Dto {
     lat: f64,
     lon: f64,
     note: String,
+    altitude: Option<f64>,
+    speed: Option<f64>,
+    heading: Option<f64>,
+    accuracy: Option<f64>,
+    satellites: Option<u8>,
+    battery: Option<f64>,
+    recorded_at: DateTime<Utc>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
@@ -32,6 +49,201 @@ struct PingParams {
     lat: f64,
     lon: f64,
     note: String,
+    altitude: Option<f64>,
+    speed: Option<f64>,
+    heading: Option<f64>,
+    accuracy: Option<f64>,
+    satellites: Option<u8>,
+    battery: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_timestamp")]
+    recorded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FilterParams {
+    /// `minLon,minLat,maxLon,maxLat`, crossing the antimeridian when `minLon > maxLon`
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    bbox: Option<String>,
+    /// `lat,lon` of the center of a circle of `radius_m` meters
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    near: Option<String>,
+    radius_m: Option<f64>,
+    /// Tolerance in meters of a Ramer-Douglas-Peucker simplification of each track
+    simplify: Option<f64>,
+    /// Upper bound of points per track, reached by Visvalingam-Whyatt
+    max_points: Option<usize>,
+    #[serde(flatten)]
+    range: RangeParams,
+}
+
+/// Pings a simplification may load, the whole selection is held in memory while it runs
+const MAX_SIMPLIFIED_PINGS: u64 = 200_000;
+
+/// Great-circle distance from `pings`.`lat`/`lon`, bound to radius, lat, lat and lon
+const HAVERSINE: &str = "2 * ? * ASIN(LEAST(1, SQRT(\
+    POW(SIN(RADIANS(`pings`.`lat` - ?) / 2), 2) + \
+    COS(RADIANS(?)) * COS(RADIANS(`pings`.`lat`)) * POW(SIN(RADIANS(`pings`.`lon` - ?) / 2), 2)\
+)))";
+
+fn coordinates<const N: usize>(value: &str) -> Option<[f64; N]> {
+    let values = value
+        .split(',')
+        .map(|v| v.trim().parse::<f64>().ok().filter(|v| v.is_finite()))
+        .collect::<Option<Vec<_>>>()?;
+
+    values.try_into().ok()
+}
+
+/// Narrows `query` to `bbox`, which the `idx_lat`/`idx_lon` indexes can answer
+fn within(query: Select<Pings>, bbox: Bbox) -> Select<Pings> {
+    let lon = if bbox.crosses_antimeridian() {
+        Condition::any()
+            .add(pings::Column::Lon.gte(bbox.min_lon))
+            .add(pings::Column::Lon.lte(bbox.max_lon))
+    } else {
+        Condition::all().add(pings::Column::Lon.between(bbox.min_lon, bbox.max_lon))
+    };
+
+    query
+        .filter(pings::Column::Lat.between(bbox.min_lat, bbox.max_lat))
+        .filter(lon)
+}
+
+impl FilterParams {
+    pub fn apply(&self, mut query: Select<Pings>) -> Result<Select<Pings>> {
+        let range = self.range.range()?;
+
+        if self
+            .simplify
+            .is_some_and(|tolerance| !(tolerance.is_finite() && tolerance > 0.0))
+        {
+            return Err(Error::BadRequest("simplify must be positive".into()));
+        }
+
+        if self.max_points.is_some_and(|max_points| max_points < 2) {
+            return Err(Error::BadRequest("max_points must be at least 2".into()));
+        }
+
+        if let Some(from) = range.from {
+            query = query.filter(pings::Column::RecordedAt.gte(from));
+        }
+
+        if let Some(to) = range.to {
+            query = query.filter(pings::Column::RecordedAt.lte(to));
+        }
+
+        if let Some(bbox) = &self.bbox {
+            let bbox = match coordinates(bbox) {
+                Some([min_lon, min_lat, max_lon, max_lat])
+                    if geo::valid(min_lat, min_lon)
+                        && geo::valid(max_lat, max_lon)
+                        && min_lat <= max_lat =>
+                {
+                    Bbox {
+                        min_lon,
+                        min_lat,
+                        max_lon,
+                        max_lat,
+                    }
+                }
+                _ => {
+                    return Err(Error::BadRequest(
+                        "bbox must be minLon,minLat,maxLon,maxLat".into(),
+                    ));
+                }
+            };
+
+            query = within(query, bbox);
+        }
+
+        match (&self.near, self.radius_m) {
+            (Some(near), Some(radius_m)) => {
+                let Some([lat, lon]) = coordinates(near).filter(|&[lat, lon]| geo::valid(lat, lon))
+                else {
+                    return Err(Error::BadRequest("near must be lat,lon".into()));
+                };
+
+                if !(radius_m.is_finite() && radius_m > 0.0) {
+                    return Err(Error::BadRequest("radius_m must be positive".into()));
+                }
+
+                // INFO: The coarse box uses the indexes, the exact distance only runs on what's left
+                query =
+                    within(query, Bbox::around(lat, lon, radius_m)).filter(Expr::cust_with_values(
+                        format!("{HAVERSINE} <= ?"),
+                        [EARTH_RADIUS_M, lat, lat, lon, radius_m],
+                    ));
+            }
+            (Some(_), None) => return Err(Error::BadRequest("near requires radius_m".into())),
+            (None, Some(_)) => return Err(Error::BadRequest("radius_m requires near".into())),
+            (None, None) => {}
+        }
+
+        Ok(query)
+    }
+
+    /// Runs the simplification, if any was asked for, over the tracks selected by `query`.
+    /// Returns the ids of the remaining pings ordered by tracker and time. Selections of more
+    /// than [`MAX_SIMPLIFIED_PINGS`] are refused.
+    pub async fn reduce(
+        &self,
+        db: &DatabaseConnection,
+        query: Select<Pings>,
+    ) -> Result<Option<Vec<u64>>> {
+        if self.simplify.is_none() && self.max_points.is_none() {
+            return Ok(None);
+        }
+
+        let rows: Vec<(u64, u64, f64, f64)> = query
+            .select_only()
+            .columns([
+                pings::Column::Id,
+                pings::Column::TrackerId,
+                pings::Column::Lat,
+                pings::Column::Lon,
+            ])
+            .order_by_asc(pings::Column::TrackerId)
+            .order_by_asc(pings::Column::RecordedAt)
+            .order_by_asc(pings::Column::Id)
+            .limit(MAX_SIMPLIFIED_PINGS + 1)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        if rows.len() as u64 > MAX_SIMPLIFIED_PINGS {
+            return Err(Error::BadRequest(format!(
+                "Simplifying is limited to {MAX_SIMPLIFIED_PINGS} pings, narrow the range"
+            )));
+        }
+
+        let mut kept = Vec::new();
+
+        for track in rows.chunk_by(|a, b| a.1 == b.1) {
+            let points: Vec<_> = track
+                .iter()
+                .map(|&(_, _, lat, lon)| Point { lat, lon })
+                .collect();
+
+            let mut indices = match self.simplify {
+                Some(tolerance) => geo::douglas_peucker(&points, tolerance),
+                None => (0..points.len()).collect(),
+            };
+
+            if let Some(max_points) = self.max_points
+                && indices.len() > max_points
+            {
+                let remaining: Vec<_> = indices.iter().map(|&i| points[i]).collect();
+                indices = geo::visvalingam(&remaining, max_points)
+                    .into_iter()
+                    .map(|i| indices[i])
+                    .collect();
+            }
+
+            kept.extend(indices.into_iter().map(|i| track[i].0));
+        }
+
+        Ok(Some(kept))
+    }
 }
 
 pub fn routes() -> Router<AppState> {
@@ -41,60 +253,115 @@ pub fn routes() -> Router<AppState> {
         .route("/pings/count", get(count))
 }
 
-fn query(params: &QueryParams) -> Select<Pings> {
+/// The pings of the caller's trackers, narrowed by `params` and `filters`
+pub fn query(user_id: u64, params: &QueryParams, filters: &FilterParams) -> Result<Select<Pings>> {
     let q = params.q.clone().unwrap_or_default();
-    let query = Pings::find();
+    let query = filters.apply(
+        Pings::find()
+            .inner_join(Trackers)
+            .filter(trackers::Column::UserId.eq(user_id)),
+    )?;
 
     if q.is_empty() {
-        return query;
+        return Ok(query);
     }
 
-    query.filter(
+    Ok(query.filter(
         Condition::any()
             .add(pings::Column::Id.eq(&q))
             .add(pings::Column::TrackerId.eq(&q)),
-    )
+    ))
 }
 
 async fn index(
+    Extension(auth): Extension<AuthClaim>,
     State(state): State<AppState>,
     Query(params): Query<QueryParams>,
+    Query(filters): Query<FilterParams>,
 ) -> Result<Json<Vec<Dto>>> {
     let (skip, take) = skippy::skip(params.skip, params.take);
-    let col = skippy::column(params.sort.clone(), pings::Column::UpdatedAt);
+    let col = skippy::column(params.sort.clone(), pings::Column::RecordedAt);
     let ord = skippy::order(params.desc, true);
 
-    let pings = query(&params)
-        .offset(skip)
-        .limit(take)
-        .order_by(col, ord)
-        .into_model::<Dto>()
-        .all(&state.db)
-        .await?;
+    let query = query(auth.user_id, &params, &filters)?;
+
+    // INFO: A simplified track is paged in the order it was recorded, sorting it makes no sense
+    let pings = match filters.reduce(&state.db, query.clone()).await? {
+        Some(ids) => {
+            let page: Vec<u64> = ids
+                .into_iter()
+                .skip(skip as usize)
+                .take(take as usize)
+                .collect();
+
+            Pings::find()
+                .filter(pings::Column::Id.is_in(page))
+                .order_by_asc(pings::Column::TrackerId)
+                .order_by_asc(pings::Column::RecordedAt)
+                .order_by_asc(pings::Column::Id)
+                .into_model::<Dto>()
+                .all(&state.db)
+                .await?
+        }
+        None => {
+            query
+                .offset(skip)
+                .limit(take)
+                .order_by(col, ord)
+                .into_model::<Dto>()
+                .all(&state.db)
+                .await?
+        }
+    };
 
     Ok(Json(pings))
 }
 
 async fn count(
+    Extension(auth): Extension<AuthClaim>,
     State(state): State<AppState>,
     Query(params): Query<QueryParams>,
+    Query(filters): Query<FilterParams>,
 ) -> Result<Json<u64>> {
-    let count = query(&params).count(&state.db).await?;
+    let query = query(auth.user_id, &params, &filters)?;
+
+    let count = match filters.reduce(&state.db, query.clone()).await? {
+        Some(ids) => ids.len() as u64,
+        None => query.count(&state.db).await?,
+    };
 
     Ok(Json(count))
 }
 
-async fn store(State(state): State<AppState>, Json(params): Json<PingParams>) -> Result<Json<u64>> {
-    let ping = pings::ActiveModel {
-        tracker_id: Set(params.tracker_id),
-        lat: Set(params.lat),
-        lon: Set(params.lon),
-        note: Set(params.note),
+async fn store(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Json(params): Json<PingParams>,
+) -> Result<Json<u64>> {
+    let tracker = Trackers::find_by_id(params.tracker_id)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
 
-        ..Default::default()
+    let fix = Fix {
+        lat: params.lat,
+        lon: params.lon,
+        note: params.note,
+        altitude: params.altitude,
+        speed: params.speed,
+        heading: params.heading,
+        accuracy: params.accuracy,
+        satellites: params.satellites,
+        battery: params.battery,
+        recorded_at: params.recorded_at,
+    };
+
+    if let Err(err) = fix.validate(&tracker) {
+        return Err(Error::BadRequest(err));
     }
-    .insert(&state.db)
-    .await?;
 
-    Ok(Json(ping.id))
+    let (created, _) = ingest::store(&state, &tracker, vec![fix]).await?;
+
+    Ok(Json(created[0]))
 }
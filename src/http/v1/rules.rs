@@ -0,0 +1,232 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    routing::{delete, get, post, put},
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseTransaction, EntityTrait,
+    FromQueryResult, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    AppState, Error, Response, Result,
+    alerts::rules as engine,
+    auth::AuthClaim,
+    entity::{
+        prelude::{Rules, Trackers},
+        rules,
+        sea_orm_active_enums::RuleKind,
+        trackers,
+    },
+};
+
+#[derive(Serialize, FromQueryResult)]
+struct Dto {
+    id: u64,
+    tracker_id: u64,
+    kind: RuleKind,
+    threshold: f64,
+    duration_s: u32,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    notify: bool,
+    enabled: bool,
+    triggered_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// `threshold` is in km/h for `speed`, percent for `battery` and meters for `accuracy` and for
+/// how far a `parked` tracker may drift from where it was when the rule was saved
+#[derive(Debug, Deserialize, Validate)]
+struct RuleParams {
+    kind: RuleKind,
+    threshold: f64,
+    /// How long the condition must hold before an alert is raised
+    #[validate(range(max = 86_400))]
+    duration_s: Option<u32>,
+    /// Whether to mail the owner, alerts always go out to webhooks
+    notify: Option<bool>,
+    enabled: Option<bool>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/trackers/{id}/rules", get(index))
+        .route("/trackers/{id}/rules", post(store))
+        .route("/trackers/{id}/rules/{rule_id}", get(show))
+        .route("/trackers/{id}/rules/{rule_id}", put(update))
+        .route("/trackers/{id}/rules/{rule_id}", delete(destroy))
+}
+
+impl RuleParams {
+    /// Validates the rule into `rule` and re-arms it, a parked rule anchors at the tracker's
+    /// last position
+    fn apply(self, tracker: &trackers::Model, rule: &mut rules::ActiveModel) -> Result<()> {
+        if let Err(err) = self.validate() {
+            return Err(Error::BadRequest(err.to_string()));
+        }
+
+        let valid = match self.kind {
+            RuleKind::Battery => self.threshold > 0.0 && self.threshold <= 100.0,
+            _ => self.threshold.is_finite() && self.threshold > 0.0,
+        };
+
+        if !valid {
+            return Err(Error::BadRequest(match self.kind {
+                RuleKind::Battery => "threshold must be between 0 and 100".into(),
+                _ => "threshold must be positive".into(),
+            }));
+        }
+
+        let (lat, lon) = match self.kind {
+            RuleKind::Parked => match (tracker.last_lat, tracker.last_lon) {
+                (Some(lat), Some(lon)) => (Some(lat), Some(lon)),
+                _ => {
+                    return Err(Error::BadRequest(
+                        "Tracker has no position to be parked at".into(),
+                    ));
+                }
+            },
+            _ => (None, None),
+        };
+
+        rule.kind = Set(self.kind);
+        rule.threshold = Set(self.threshold);
+        rule.duration_s = Set(self.duration_s.unwrap_or_default());
+        rule.lat = Set(lat);
+        rule.lon = Set(lon);
+        rule.notify = Set(self.notify.unwrap_or(true));
+        rule.enabled = Set(self.enabled.unwrap_or(true));
+        rule.breach_started_at = Set(None);
+        rule.triggered_at = Set(None);
+
+        Ok(())
+    }
+}
+
+async fn tracker(state: &AppState, user_id: u64, id: u64) -> Result<trackers::Model> {
+    Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+/// The tracker and the rule, with the tracker row locked so ingest doesn't evaluate the rule
+/// while it changes
+async fn lock(
+    txn: &DatabaseTransaction,
+    user_id: u64,
+    id: u64,
+    rule_id: u64,
+) -> Result<(trackers::Model, rules::Model)> {
+    let tracker = Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(user_id))
+        .lock_exclusive()
+        .one(txn)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let rule = Rules::find_by_id(rule_id)
+        .filter(rules::Column::TrackerId.eq(tracker.id))
+        .one(txn)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok((tracker, rule))
+}
+
+async fn index(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Vec<Dto>>> {
+    let tracker = tracker(&state, auth.user_id, id).await?;
+
+    let rules = Rules::find()
+        .filter(rules::Column::TrackerId.eq(tracker.id))
+        .order_by_asc(rules::Column::Id)
+        .into_model::<Dto>()
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(rules))
+}
+
+async fn store(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Json(params): Json<RuleParams>,
+) -> Result<Response> {
+    let tracker = tracker(&state, auth.user_id, id).await?;
+
+    let mut rule = rules::ActiveModel {
+        tracker_id: Set(tracker.id),
+
+        ..Default::default()
+    };
+    params.apply(&tracker, &mut rule)?;
+
+    let rule = rule.insert(&state.db).await?;
+
+    Ok(Response::Created(rule.id))
+}
+
+async fn show(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path((id, rule_id)): Path<(u64, u64)>,
+) -> Result<Json<Dto>> {
+    let tracker = tracker(&state, auth.user_id, id).await?;
+
+    let rule = Rules::find_by_id(rule_id)
+        .filter(rules::Column::TrackerId.eq(tracker.id))
+        .into_model::<Dto>()
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(rule))
+}
+
+/// Replaces the rule and re-arms it, resolving the alert it had open
+async fn update(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path((id, rule_id)): Path<(u64, u64)>,
+    Json(params): Json<RuleParams>,
+) -> Result<Response> {
+    let txn = state.db.begin().await?;
+    let (tracker, rule) = lock(&txn, auth.user_id, id, rule_id).await?;
+
+    let mut rule = rule.into_active_model();
+    params.apply(&tracker, &mut rule)?;
+    rule.updated_at = Set(Utc::now());
+    let rule = rule.update(&txn).await?;
+
+    engine::resolve(&txn, rule.id, Utc::now()).await?;
+    txn.commit().await?;
+
+    Ok(Response::Accepted)
+}
+
+/// Deletes the rule, the alerts it raised stay in the history of the tracker
+async fn destroy(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path((id, rule_id)): Path<(u64, u64)>,
+) -> Result<Response> {
+    let txn = state.db.begin().await?;
+    let (_, rule) = lock(&txn, auth.user_id, id, rule_id).await?;
+
+    engine::resolve(&txn, rule.id, Utc::now()).await?;
+    Rules::delete_by_id(rule.id).exec(&txn).await?;
+    txn.commit().await?;
+
+    Ok(Response::NoContent)
+}
@@ -0,0 +1,93 @@
+use axum::{
+    Extension, Router,
+    extract::{Path, State},
+    routing::{delete, post, put},
+};
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, IntoActiveModel, QueryFilter,
+    Select,
+};
+
+use crate::{
+    AppState, Error, Response, Result,
+    auth::{self, AuthClaim},
+    entity::{prelude::Trackers, trackers},
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/trackers/{id}/secret", post(store))
+        .route("/trackers/{id}/secret", put(update))
+        .route("/trackers/{id}/secret", delete(destroy))
+}
+
+fn query_one(id: u64, user_id: u64) -> Select<Trackers> {
+    Trackers::find_by_id(id).filter(trackers::Column::UserId.eq(user_id))
+}
+
+async fn store(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Response> {
+    let tracker = query_one(id, auth.user_id)
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if tracker.secret.is_some() {
+        return Err(Error::BadRequest("Tracker already has a secret".into()));
+    }
+
+    let secret = auth::generate_secret();
+
+    let mut tracker = tracker.into_active_model();
+    tracker.secret = Set(Some(auth::hash_password(&secret)?));
+    tracker.updated_at = Set(Utc::now());
+    tracker.save(&state.db).await?;
+
+    Ok(Response::Secret(secret))
+}
+
+async fn update(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Response> {
+    let tracker = query_one(id, auth.user_id)
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if tracker.secret.is_none() {
+        return Err(Error::NotFound);
+    }
+
+    let secret = auth::generate_secret();
+
+    let mut tracker = tracker.into_active_model();
+    tracker.secret = Set(Some(auth::hash_password(&secret)?));
+    tracker.updated_at = Set(Utc::now());
+    tracker.save(&state.db).await?;
+
+    Ok(Response::Secret(secret))
+}
+
+async fn destroy(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Response> {
+    let tracker = query_one(id, auth.user_id)
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let mut tracker = tracker.into_active_model();
+    tracker.secret = Set(None);
+    tracker.updated_at = Set(Utc::now());
+    tracker.save(&state.db).await?;
+
+    Ok(Response::NoContent)
+}
@@ -0,0 +1,97 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::get,
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{ColumnTrait, EntityTrait, FromQueryResult, QueryFilter, QueryOrder, QuerySelect};
+use serde::Serialize;
+
+use crate::{
+    AppState, Error, Result,
+    entity::{
+        pings,
+        prelude::{Pings, ShareLinks, Trackers},
+        share_links,
+    },
+    geo,
+};
+
+/// Pings a share link shows at most
+const RECENT_PINGS: u64 = 100;
+
+#[derive(Serialize)]
+struct Dto {
+    name: String,
+    pings: Vec<PingDto>,
+}
+
+#[derive(Serialize, FromQueryResult)]
+struct PingDto {
+    lat: f64,
+    lon: f64,
+    recorded_at: DateTime<Utc>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/shared/{token}", get(show))
+}
+
+/// The name of the shared tracker and its most recent pings in the link's window, in recorded
+/// order. Unknown, revoked and expired links are all not found.
+async fn show(State(state): State<AppState>, Path(token): Path<String>) -> Result<Json<Dto>> {
+    let (link, tracker) = ShareLinks::find()
+        .find_also_related(Trackers)
+        .filter(share_links::Column::Token.eq(token))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let tracker = tracker.ok_or(Error::NotFound)?;
+
+    if link
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(Error::NotFound);
+    }
+
+    let mut query = Pings::find()
+        .select_only()
+        .columns([
+            pings::Column::Lat,
+            pings::Column::Lon,
+            pings::Column::RecordedAt,
+        ])
+        .filter(pings::Column::TrackerId.eq(tracker.id));
+
+    if let Some(from) = link.window_from {
+        query = query.filter(pings::Column::RecordedAt.gte(from));
+    }
+
+    if let Some(to) = link.window_to {
+        query = query.filter(pings::Column::RecordedAt.lte(to));
+    }
+
+    let mut pings = query
+        .order_by_desc(pings::Column::RecordedAt)
+        .order_by_desc(pings::Column::Id)
+        .limit(RECENT_PINGS)
+        .into_model::<PingDto>()
+        .all(&state.db)
+        .await?;
+
+    pings.reverse();
+
+    if let Some(precision) = link.precision {
+        for ping in &mut pings {
+            ping.lat = geo::round(ping.lat, precision);
+            ping.lon = geo::round(ping.lon, precision);
+        }
+    }
+
+    Ok(Json(Dto {
+        name: tracker.name,
+        pings,
+    }))
+}
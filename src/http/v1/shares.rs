@@ -0,0 +1,156 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, FromQueryResult, QueryFilter,
+    QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::{
+    AppState, Error, Response, Result,
+    auth::{self, AuthClaim},
+    entity::{
+        prelude::{ShareLinks, Trackers},
+        share_links, trackers,
+    },
+};
+
+#[derive(Serialize, FromQueryResult)]
+struct Dto {
+    id: u64,
+    token: String,
+    expires_at: Option<DateTime<Utc>>,
+    window_from: Option<DateTime<Utc>>,
+    window_to: Option<DateTime<Utc>>,
+    precision: Option<u8>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct ShareParams {
+    /// When the link stops working, never when absent
+    expires_at: Option<DateTime<Utc>>,
+    /// Only pings recorded in this window are shown
+    window_from: Option<DateTime<Utc>>,
+    window_to: Option<DateTime<Utc>>,
+    /// Decimals the coordinates are rounded to, all of them when absent
+    #[validate(range(max = 6))]
+    precision: Option<u8>,
+}
+
+impl From<share_links::Model> for Dto {
+    fn from(link: share_links::Model) -> Self {
+        Dto {
+            id: link.id,
+            token: link.token,
+            expires_at: link.expires_at,
+            window_from: link.window_from,
+            window_to: link.window_to,
+            precision: link.precision,
+            created_at: link.created_at,
+        }
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/trackers/{id}/shares", get(index))
+        .route("/trackers/{id}/shares", post(store))
+        .route("/trackers/{id}/shares/{share_id}", delete(destroy))
+}
+
+async fn tracker(state: &AppState, user_id: u64, id: u64) -> Result<trackers::Model> {
+    Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+/// The share links of a tracker, expired ones included, newest first
+async fn index(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Vec<Dto>>> {
+    let tracker = tracker(&state, auth.user_id, id).await?;
+
+    let links = ShareLinks::find()
+        .filter(share_links::Column::TrackerId.eq(tracker.id))
+        .order_by_desc(share_links::Column::Id)
+        .into_model::<Dto>()
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(links))
+}
+
+async fn store(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Json(params): Json<ShareParams>,
+) -> Result<(StatusCode, Json<Dto>)> {
+    if let Err(err) = params.validate() {
+        return Err(Error::BadRequest(err.to_string()));
+    }
+
+    if params
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(Error::BadRequest("expires_at must be in the future".into()));
+    }
+
+    if let (Some(from), Some(to)) = (params.window_from, params.window_to)
+        && from > to
+    {
+        return Err(Error::BadRequest(
+            "window_from must not be after window_to".into(),
+        ));
+    }
+
+    let tracker = tracker(&state, auth.user_id, id).await?;
+
+    let link = share_links::ActiveModel {
+        tracker_id: Set(tracker.id),
+        token: Set(auth::generate_secret()),
+        expires_at: Set(params.expires_at),
+        window_from: Set(params.window_from),
+        window_to: Set(params.window_to),
+        precision: Set(params.precision),
+
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(Dto::from(link))))
+}
+
+/// Revokes the link, it stops working right away
+async fn destroy(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path((id, share_id)): Path<(u64, u64)>,
+) -> Result<Response> {
+    let tracker = tracker(&state, auth.user_id, id).await?;
+
+    let deleted = ShareLinks::delete_many()
+        .filter(share_links::Column::Id.eq(share_id))
+        .filter(share_links::Column::TrackerId.eq(tracker.id))
+        .exec(&state.db)
+        .await?;
+
+    if deleted.rows_affected == 0 {
+        return Err(Error::NotFound);
+    }
+
+    Ok(Response::NoContent)
+}
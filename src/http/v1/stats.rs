@@ -0,0 +1,76 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    routing::get,
+};
+use chrono::Duration;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState, Error, Result,
+    analysis::{
+        self,
+        stats::{self, Bucket, Stats},
+    },
+    auth::AuthClaim,
+    entity::{prelude::Trackers, trackers},
+    http::params::{RangeParams, deserialize_trim},
+};
+
+#[derive(Debug, Deserialize)]
+struct StatsParams {
+    #[serde(default, deserialize_with = "deserialize_trim")]
+    bucket: Option<String>,
+    #[serde(flatten)]
+    range: RangeParams,
+}
+
+#[derive(Serialize)]
+struct Dto {
+    /// Lifetime distance of the tracker in meters
+    odometer_m: f64,
+    total: Stats,
+    buckets: Vec<Stats>,
+}
+
+/// Longest range summarized in one request
+const MAX_DAYS: i64 = 366;
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/trackers/{id}/stats", get(show))
+}
+
+/// Statistics over the requested range, the last 30 days unless one is given and at most
+/// [`MAX_DAYS`]
+async fn show(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(params): Query<StatsParams>,
+) -> Result<Json<Dto>> {
+    let (from, to) = params
+        .range
+        .range()?
+        .bounded(Duration::days(30), MAX_DAYS)?;
+    let bucket = match params.bucket {
+        Some(bucket) => bucket
+            .parse::<Bucket>()
+            .map_err(|_| Error::BadRequest("bucket must be one of day, week or month".into()))?,
+        None => Bucket::Day,
+    };
+
+    let tracker = Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let samples = analysis::samples(&state.db, tracker.id, from, to).await?;
+
+    Ok(Json(Dto {
+        odometer_m: tracker.odometer_m,
+        total: stats::total(&samples, from),
+        buckets: stats::buckets(&samples, bucket),
+    }))
+}
@@ -1,19 +1,25 @@
 use crate::{
     AppState, Error, Response,
     auth::AuthClaim,
-    entity::{prelude::Trackers, trackers},
+    entity::{
+        pings,
+        prelude::{Pings, Trackers},
+        trackers,
+    },
+    events::{self, Event},
     http::params::QueryParams,
-    skippy, util,
+    skippy, util, webhooks,
 };
 use axum::{
     Extension, Json, Router,
     extract::{Path, Query, State},
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
 };
 use chrono::{DateTime, Utc};
 use sea_orm::{
     ActiveModelTrait, ActiveValue::Set, ColumnTrait, Condition, EntityTrait, FromQueryResult,
-    IntoActiveModel, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+    IntoActiveModel, JoinType, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, Select,
+    TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -26,15 +32,66 @@ struct Dto {
     slug: Option<String>,
     name: String,
     desc: String,
+    imei: Option<String>,
+    last_ping_at: Option<DateTime<Utc>>,
+    last_lat: Option<f64>,
+    last_lon: Option<f64>,
+    odometer_m: f64,
+    expected_interval_s: Option<u32>,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
 }
 
+#[derive(FromQueryResult)]
+struct PositionRow {
+    id: u64,
+    name: String,
+    ping_id: Option<u64>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    note: Option<String>,
+    altitude: Option<f64>,
+    speed: Option<f64>,
+    heading: Option<f64>,
+    accuracy: Option<f64>,
+    satellites: Option<u8>,
+    battery: Option<f64>,
+    recorded_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+struct PositionDto {
+    id: u64,
+    slug: String,
+    name: String,
+    ping: Option<PingDto>,
+}
+
+#[derive(Serialize)]
+struct PingDto {
+    id: u64,
+    lat: f64,
+    lon: f64,
+    note: String,
+    altitude: Option<f64>,
+    speed: Option<f64>,
+    heading: Option<f64>,
+    accuracy: Option<f64>,
+    satellites: Option<u8>,
+    battery: Option<f64>,
+    recorded_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 struct TrackerParams {
     #[validate(length(min = 1))]
     name: String,
     desc: String,
+    #[validate(length(min = 10, max = 20))]
+    imei: Option<String>,
+    /// Seconds without a ping after which the owner is alerted, from a minute to 30 days
+    #[validate(range(min = 60, max = 2_592_000))]
+    expected_interval_s: Option<u32>,
 }
 
 pub fn routes() -> Router<AppState> {
@@ -42,13 +99,16 @@ pub fn routes() -> Router<AppState> {
         .route("/trackers", get(index))
         .route("/trackers", post(store))
         .route("/trackers/count", get(count))
+        .route("/trackers/positions", get(positions))
         .route("/trackers/{id}", get(show))
+        .route("/trackers/{id}", put(update))
         .route("/trackers/{id}", delete(destroy))
 }
 
-fn query(params: &QueryParams) -> Select<Trackers> {
+/// The caller's trackers, narrowed by `params`
+fn query(user_id: u64, params: &QueryParams) -> Select<Trackers> {
     let q = params.q.clone().unwrap_or_default();
-    let query = Trackers::find();
+    let query = Trackers::find().filter(trackers::Column::UserId.eq(user_id));
 
     if q.is_empty() {
         return query;
@@ -70,6 +130,7 @@ fn query_select(query: Select<Trackers>) -> Select<Trackers> {
 }
 
 async fn index(
+    Extension(auth): Extension<AuthClaim>,
     State(state): State<AppState>,
     Query(params): Query<QueryParams>,
 ) -> Result<Json<Vec<Dto>>> {
@@ -77,7 +138,7 @@ async fn index(
     let col = skippy::column(params.sort.clone(), trackers::Column::UpdatedAt);
     let ord = skippy::order(params.desc, true);
 
-    let mut trackers = query(&params)
+    let mut trackers = query(auth.user_id, &params)
         .offset(skip)
         .limit(take)
         .order_by(col, ord)
@@ -94,14 +155,80 @@ async fn index(
 }
 
 async fn count(
+    Extension(auth): Extension<AuthClaim>,
     State(state): State<AppState>,
     Query(params): Query<QueryParams>,
 ) -> Result<Json<u64>> {
-    let count = query(&params).count(&state.db).await?;
+    let count = query(auth.user_id, &params).count(&state.db).await?;
 
     Ok(Json(count))
 }
 
+/// The caller's trackers with their most recent ping, found through `last_ping_id`
+async fn positions(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<PositionDto>>> {
+    let last_ping = Trackers::belongs_to(Pings)
+        .from(trackers::Column::LastPingId)
+        .to(pings::Column::Id)
+        .into();
+
+    let rows = Trackers::find()
+        .select_only()
+        .columns([trackers::Column::Id, trackers::Column::Name])
+        .column_as(pings::Column::Id, "ping_id")
+        .columns([
+            pings::Column::Lat,
+            pings::Column::Lon,
+            pings::Column::Note,
+            pings::Column::Altitude,
+            pings::Column::Speed,
+            pings::Column::Heading,
+            pings::Column::Accuracy,
+            pings::Column::Satellites,
+            pings::Column::Battery,
+            pings::Column::RecordedAt,
+        ])
+        .join(JoinType::LeftJoin, last_ping)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .order_by_asc(trackers::Column::Name)
+        .into_model::<PositionRow>()
+        .all(&state.db)
+        .await?;
+
+    let sqids = util::sqids()?;
+    let mut positions = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        let ping = match (row.ping_id, row.lat, row.lon, row.recorded_at) {
+            (Some(id), Some(lat), Some(lon), Some(recorded_at)) => Some(PingDto {
+                id,
+                lat,
+                lon,
+                note: row.note.unwrap_or_default(),
+                altitude: row.altitude,
+                speed: row.speed,
+                heading: row.heading,
+                accuracy: row.accuracy,
+                satellites: row.satellites,
+                battery: row.battery,
+                recorded_at,
+            }),
+            _ => None,
+        };
+
+        positions.push(PositionDto {
+            id: row.id,
+            slug: sqids.encode(&[row.id])?,
+            name: row.name,
+            ping,
+        });
+    }
+
+    Ok(Json(positions))
+}
+
 async fn store(
     Extension(auth): Extension<AuthClaim>,
     State(state): State<AppState>,
@@ -111,21 +238,107 @@ async fn store(
         return Err(Error::BadRequest(err.to_string()));
     }
 
+    if let Some(imei) = &params.imei {
+        imei_available(&state, imei, None).await?;
+    }
+
+    let txn = state.db.begin().await?;
+
     let tracker = trackers::ActiveModel {
         user_id: Set(auth.user_id),
         name: Set(params.name),
         desc: Set(params.desc),
+        imei: Set(params.imei),
+        expected_interval_s: Set(params.expected_interval_s),
 
         ..Default::default()
     }
-    .insert(&state.db)
+    .insert(&txn)
     .await?;
 
-    Ok(Json(tracker.id))
+    let id = tracker.id;
+    let event = Event::TrackerCreated {
+        user_id: tracker.user_id,
+        tracker_id: tracker.id,
+        name: tracker.name,
+        desc: tracker.desc,
+        imei: tracker.imei,
+    };
+
+    webhooks::enqueue(&txn, std::slice::from_ref(&event)).await?;
+    txn.commit().await?;
+
+    events::publish(&state, event);
+
+    Ok(Json(id))
 }
 
-async fn show(State(state): State<AppState>, Path(id): Path<u64>) -> Result<Json<Dto>> {
+async fn update(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Json(params): Json<TrackerParams>,
+) -> Result<Response> {
+    if let Err(err) = params.validate() {
+        return Err(Error::BadRequest(err.to_string()));
+    }
+
+    let tracker = query_one(id)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    if let Some(imei) = &params.imei {
+        imei_available(&state, imei, Some(tracker.id)).await?;
+    }
+
+    let mut tracker = tracker.into_active_model();
+    tracker.name = Set(params.name);
+    tracker.desc = Set(params.desc);
+    tracker.imei = Set(params.imei);
+    tracker.expected_interval_s = Set(params.expected_interval_s);
+    tracker.updated_at = Set(Utc::now());
+
+    let txn = state.db.begin().await?;
+    let tracker = tracker.update(&txn).await?;
+
+    let event = Event::TrackerUpdated {
+        user_id: tracker.user_id,
+        tracker_id: tracker.id,
+        name: tracker.name,
+        desc: tracker.desc,
+        imei: tracker.imei,
+    };
+
+    webhooks::enqueue(&txn, std::slice::from_ref(&event)).await?;
+    txn.commit().await?;
+
+    events::publish(&state, event);
+
+    Ok(Response::Accepted)
+}
+
+async fn imei_available(state: &AppState, imei: &str, id: Option<u64>) -> Result<()> {
+    let mut query = Trackers::find().filter(trackers::Column::Imei.eq(imei));
+    if let Some(id) = id {
+        query = query.filter(trackers::Column::Id.ne(id));
+    }
+
+    if query.one(&state.db).await?.is_some() {
+        return Err(Error::BadRequest("IMEI is taken".to_string()));
+    }
+
+    Ok(())
+}
+
+async fn show(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Dto>> {
     let tracker = query_select(query_one(id))
+        .filter(trackers::Column::UserId.eq(auth.user_id))
         .into_model::<Dto>()
         .one(&state.db)
         .await?
@@ -134,12 +347,30 @@ async fn show(State(state): State<AppState>, Path(id): Path<u64>) -> Result<Json
     Ok(Json(tracker))
 }
 
-async fn destroy(State(state): State<AppState>, Path(id): Path<u64>) -> Result<Response> {
-    let tracker = query_one(id).one(&state.db).await?.ok_or(Error::NotFound)?;
+/// Deletes the tracker along with its pings, rules, alerts and share links
+async fn destroy(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Response> {
+    let tracker = query_one(id)
+        .filter(trackers::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let txn = state.db.begin().await?;
+    Trackers::delete_by_id(tracker.id).exec(&txn).await?;
 
-    let mut tracker = tracker.into_active_model();
-    tracker.updated_at = Set(Utc::now());
-    tracker.save(&state.db).await?;
+    let event = Event::TrackerDeleted {
+        user_id: tracker.user_id,
+        tracker_id: tracker.id,
+    };
+
+    webhooks::enqueue(&txn, std::slice::from_ref(&event)).await?;
+    txn.commit().await?;
+
+    events::publish(&state, event);
 
     Ok(Response::Accepted)
 }
@@ -0,0 +1,151 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    routing::get,
+};
+use chrono::Duration;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde::Deserialize;
+
+use crate::{
+    AppState, Error, Result,
+    analysis::{
+        self,
+        trips::{self, Options, Stop, Trip},
+    },
+    auth::AuthClaim,
+    entity::{prelude::Trackers, trackers},
+    http::params::RangeParams,
+};
+
+#[derive(Debug, Deserialize)]
+struct TripParams {
+    /// Seconds of silence that end a trip
+    gap: Option<i64>,
+    /// Meters a tracker may wander and still be stopped
+    radius_m: Option<f64>,
+    /// Seconds a tracker has to stay put to be stopped
+    dwell: Option<i64>,
+    #[serde(flatten)]
+    range: RangeParams,
+}
+
+/// Longest range segmented in one request
+const MAX_DAYS: i64 = 366;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/trackers/{id}/trips", get(trips))
+        .route("/trackers/{id}/stops", get(stops))
+}
+
+/// A positive number of seconds that fits a [`Duration`]
+fn seconds(name: &str, value: i64) -> Result<Duration> {
+    Duration::try_seconds(value)
+        .filter(|_| value > 0)
+        .ok_or_else(|| Error::BadRequest(format!("{name} must be a positive number of seconds")))
+}
+
+impl TripParams {
+    fn options(&self) -> Result<Options> {
+        let mut options = Options::default();
+        if let Some(gap) = self.gap {
+            options.max_gap = seconds("gap", gap)?;
+        }
+        if let Some(radius_m) = self.radius_m {
+            if !(radius_m.is_finite() && radius_m > 0.0) {
+                return Err(Error::BadRequest("radius_m must be positive".into()));
+            }
+            options.dwell_radius_m = radius_m;
+        }
+        if let Some(dwell) = self.dwell {
+            options.min_dwell = seconds("dwell", dwell)?;
+        }
+
+        Ok(options)
+    }
+}
+
+/// Segments the tracker's pings of the requested range, the last day unless one is given and
+/// at most [`MAX_DAYS`]
+async fn analyze(
+    state: &AppState,
+    user_id: u64,
+    id: u64,
+    params: &TripParams,
+) -> Result<(Vec<Trip>, Vec<Stop>)> {
+    let (from, to) = params.range.range()?.bounded(Duration::days(1), MAX_DAYS)?;
+    let options = params.options()?;
+
+    let tracker = Trackers::find_by_id(id)
+        .filter(trackers::Column::UserId.eq(user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let samples = analysis::samples(&state.db, tracker.id, from, to).await?;
+
+    Ok(trips::segment(&samples, &options))
+}
+
+async fn trips(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(params): Query<TripParams>,
+) -> Result<Json<Vec<Trip>>> {
+    let (trips, _) = analyze(&state, auth.user_id, id, &params).await?;
+
+    Ok(Json(trips))
+}
+
+async fn stops(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(params): Query<TripParams>,
+) -> Result<Json<Vec<Stop>>> {
+    let (_, stops) = analyze(&state, auth.user_id, id, &params).await?;
+
+    Ok(Json(stops))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(gap: Option<i64>, dwell: Option<i64>) -> TripParams {
+        TripParams {
+            gap,
+            radius_m: None,
+            dwell,
+            range: RangeParams {
+                from: None,
+                to: None,
+                since: None,
+            },
+        }
+    }
+
+    #[test]
+    fn reads_options() {
+        let options = params(Some(600), Some(120)).options().unwrap();
+
+        assert_eq!(options.max_gap, Duration::minutes(10));
+        assert_eq!(options.min_dwell, Duration::minutes(2));
+    }
+
+    #[test]
+    fn rejects_durations_out_of_range() {
+        for value in [0, -1, i64::MIN, i64::MAX] {
+            assert!(matches!(
+                params(Some(value), None).options(),
+                Err(Error::BadRequest(_))
+            ));
+            assert!(matches!(
+                params(None, Some(value)).options(),
+                Err(Error::BadRequest(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post, put},
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, EntityTrait, FromQueryResult, IntoActiveModel,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use url::Url;
+use validator::Validate;
+
+use crate::{
+    AppState, Error, Response, Result,
+    auth::{self, AuthClaim},
+    entity::{
+        prelude::{Trackers, WebhookDeliveries, Webhooks},
+        sea_orm_active_enums::DeliveryStatus,
+        trackers, webhook_deliveries, webhooks,
+    },
+    events,
+    http::params::QueryParams,
+    skippy, webhooks as delivery,
+};
+
+#[derive(Serialize, FromQueryResult)]
+struct Dto {
+    id: u64,
+    url: String,
+    events: serde_json::Value,
+    tracker_ids: Option<serde_json::Value>,
+    enabled: bool,
+    failure_count: u32,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+/// The secret is only ever shown here and when it is rotated
+#[derive(Serialize)]
+struct CreatedDto {
+    id: u64,
+    secret: String,
+}
+
+#[derive(Serialize, FromQueryResult)]
+struct DeliveryDto {
+    id: u64,
+    event: String,
+    payload: String,
+    status: DeliveryStatus,
+    attempts: u32,
+    response_status: Option<u16>,
+    error: Option<String>,
+    next_attempt_at: Option<DateTime<Utc>>,
+    delivered_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+struct WebhookParams {
+    #[validate(url, length(max = 2048))]
+    url: String,
+    /// Any of [`events::NAMES`]
+    #[validate(length(min = 1))]
+    events: Vec<String>,
+    /// Only events of these trackers are delivered, all of them when absent
+    tracker_ids: Option<Vec<u64>>,
+    /// Turning a webhook back on also forgets its failures
+    enabled: Option<bool>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/webhooks", get(index))
+        .route("/webhooks", post(store))
+        .route("/webhooks/{id}", get(show))
+        .route("/webhooks/{id}", put(update))
+        .route("/webhooks/{id}", delete(destroy))
+        .route("/webhooks/{id}/secret", put(rotate))
+        .route("/webhooks/{id}/deliveries", get(deliveries))
+        .route("/webhooks/{id}/test", post(test))
+}
+
+impl WebhookParams {
+    async fn check(&self, state: &AppState, user_id: u64) -> Result<()> {
+        if let Err(err) = self.validate() {
+            return Err(Error::BadRequest(err.to_string()));
+        }
+
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            return Err(Error::BadRequest("url must be http or https".into()));
+        }
+
+        // WARN: The server posts to whatever is registered here, keep it off internal networks
+        if !state.allow_private_webhooks {
+            let url = Url::parse(&self.url).map_err(|err| Error::BadRequest(err.to_string()))?;
+            delivery::check_host(&url)
+                .await
+                .map_err(Error::BadRequest)?;
+        }
+
+        if let Some(event) = self
+            .events
+            .iter()
+            .find(|event| !events::NAMES.contains(&event.as_str()))
+        {
+            return Err(Error::BadRequest(format!(
+                "Unknown event {event}, expected any of {}",
+                events::NAMES.join(", ")
+            )));
+        }
+
+        let Some(tracker_ids) = &self.tracker_ids else {
+            return Ok(());
+        };
+
+        let tracker_ids: HashSet<_> = tracker_ids.iter().copied().collect();
+        let owned = Trackers::find()
+            .filter(trackers::Column::Id.is_in(tracker_ids.iter().copied()))
+            .filter(trackers::Column::UserId.eq(user_id))
+            .count(&state.db)
+            .await?;
+
+        if owned != tracker_ids.len() as u64 {
+            return Err(Error::NotFound);
+        }
+
+        Ok(())
+    }
+}
+
+async fn find(state: &AppState, user_id: u64, id: u64) -> Result<webhooks::Model> {
+    Webhooks::find_by_id(id)
+        .filter(webhooks::Column::UserId.eq(user_id))
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)
+}
+
+async fn index(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Query(params): Query<QueryParams>,
+) -> Result<Json<Vec<Dto>>> {
+    let (skip, take) = skippy::skip(params.skip, params.take);
+
+    let hooks = Webhooks::find()
+        .filter(webhooks::Column::UserId.eq(auth.user_id))
+        .offset(skip)
+        .limit(take)
+        .order_by_desc(webhooks::Column::Id)
+        .into_model::<Dto>()
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(hooks))
+}
+
+async fn store(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Json(params): Json<WebhookParams>,
+) -> Result<(StatusCode, Json<CreatedDto>)> {
+    params.check(&state, auth.user_id).await?;
+
+    let secret = auth::generate_secret();
+
+    let hook = webhooks::ActiveModel {
+        user_id: Set(auth.user_id),
+        url: Set(params.url),
+        secret: Set(secret.clone()),
+        events: Set(serde_json::json!(params.events)),
+        tracker_ids: Set(params.tracker_ids.map(|ids| serde_json::json!(ids))),
+        enabled: Set(params.enabled.unwrap_or(true)),
+
+        ..Default::default()
+    }
+    .insert(&state.db)
+    .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedDto {
+            id: hook.id,
+            secret,
+        }),
+    ))
+}
+
+async fn show(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Json<Dto>> {
+    let hook = Webhooks::find_by_id(id)
+        .filter(webhooks::Column::UserId.eq(auth.user_id))
+        .into_model::<Dto>()
+        .one(&state.db)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    Ok(Json(hook))
+}
+
+async fn update(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Json(params): Json<WebhookParams>,
+) -> Result<Response> {
+    params.check(&state, auth.user_id).await?;
+
+    let hook = find(&state, auth.user_id, id).await?;
+    let enabled = params.enabled.unwrap_or(hook.enabled);
+    let reenabled = enabled && !hook.enabled;
+
+    let mut hook = hook.into_active_model();
+    hook.url = Set(params.url);
+    hook.events = Set(serde_json::json!(params.events));
+    hook.tracker_ids = Set(params.tracker_ids.map(|ids| serde_json::json!(ids)));
+    hook.enabled = Set(enabled);
+    if reenabled {
+        hook.failure_count = Set(0);
+    }
+    hook.updated_at = Set(Utc::now());
+    hook.save(&state.db).await?;
+
+    Ok(Response::Accepted)
+}
+
+async fn destroy(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Response> {
+    let hook = find(&state, auth.user_id, id).await?;
+
+    Webhooks::delete_by_id(hook.id).exec(&state.db).await?;
+
+    Ok(Response::NoContent)
+}
+
+async fn rotate(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Response> {
+    let hook = find(&state, auth.user_id, id).await?;
+    let secret = auth::generate_secret();
+
+    let mut hook = hook.into_active_model();
+    hook.secret = Set(secret.clone());
+    hook.updated_at = Set(Utc::now());
+    hook.save(&state.db).await?;
+
+    Ok(Response::Secret(secret))
+}
+
+/// The delivery log of a webhook, newest first
+async fn deliveries(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+    Query(params): Query<QueryParams>,
+) -> Result<Json<Vec<DeliveryDto>>> {
+    let hook = find(&state, auth.user_id, id).await?;
+    let (skip, take) = skippy::skip(params.skip, params.take);
+
+    let deliveries = WebhookDeliveries::find()
+        .filter(webhook_deliveries::Column::WebhookId.eq(hook.id))
+        .offset(skip)
+        .limit(take)
+        .order_by_desc(webhook_deliveries::Column::Id)
+        .into_model::<DeliveryDto>()
+        .all(&state.db)
+        .await?;
+
+    Ok(Json(deliveries))
+}
+
+/// Queues a `test` delivery, whatever events the webhook subscribed to
+async fn test(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    Path(id): Path<u64>,
+) -> Result<Response> {
+    let hook = find(&state, auth.user_id, id).await?;
+
+    if !hook.enabled {
+        return Err(Error::BadRequest("Webhook is disabled".into()));
+    }
+
+    let data = serde_json::json!({ "webhook_id": hook.id });
+    delivery::enqueue_to(&state.db, &[hook], "test", &data).await?;
+    state.deliveries.notify_one();
+
+    Ok(Response::Accepted)
+}
@@ -0,0 +1,163 @@
+use axum::{
+    Extension, Router,
+    body::Bytes,
+    extract::{
+        State, WebSocketUpgrade,
+        ws::{Message, WebSocket},
+    },
+    http::{HeaderMap, header},
+    response::IntoResponse,
+    routing::get,
+};
+use sea_orm::{ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashSet, time::Duration};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::error;
+
+use crate::{
+    AppState, Error, Result,
+    auth::AuthClaim,
+    entity::{prelude::Trackers, trackers},
+    http::DEV_ORIGIN,
+};
+
+/// Seconds between pings that keep idle connections and proxies from timing out
+const HEARTBEAT_SECONDS: u64 = 30;
+
+/// What a client sends, `{"type":"subscribe","tracker_ids":[1,2]}`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Command {
+    Subscribe { tracker_ids: Vec<u64> },
+    Unsubscribe { tracker_ids: Vec<u64> },
+}
+
+/// What a client gets back next to the events of the trackers it subscribed to
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Reply {
+    Subscribed { tracker_ids: Vec<u64> },
+    Unsubscribed { tracker_ids: Vec<u64> },
+    Lagged { missed: u64 },
+    Error { msg: String },
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/ws", get(upgrade))
+}
+
+fn text<T: Serialize>(value: &T) -> Message {
+    Message::Text(serde_json::to_string(value).unwrap_or_default().into())
+}
+
+async fn upgrade(
+    Extension(auth): Extension<AuthClaim>,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Result<impl IntoResponse> {
+    // WARN: The upgrade is a GET that skips the CSRF check while the auth cookie rides along,
+    // so browsers may only connect from the SPA
+    let origin = headers
+        .get(header::ORIGIN)
+        .and_then(|origin| origin.to_str().ok());
+
+    if let Some(origin) = origin
+        && origin != state.spa_url.trim_end_matches('/')
+        && origin != DEV_ORIGIN
+    {
+        return Err(Error::Forbidden);
+    }
+
+    Ok(ws.on_upgrade(move |socket| session(state, auth.user_id, socket)))
+}
+
+async fn session(state: AppState, user_id: u64, mut socket: WebSocket) {
+    let mut rx = state.events.subscribe();
+    let mut subscriptions = HashSet::new();
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(HEARTBEAT_SECONDS));
+
+    loop {
+        let message = tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(command))) => {
+                    handle(&state, user_id, &mut subscriptions, command.as_str()).await
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            event = rx.recv() => match event {
+                Ok(event)
+                    if event.user_id() == user_id
+                        && subscriptions.contains(&event.tracker_id()) =>
+                {
+                    text(&event)
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => text(&Reply::Lagged { missed }),
+                Err(RecvError::Closed) => break,
+            },
+            _ = heartbeat.tick() => Message::Ping(Bytes::new()),
+        };
+
+        if socket.send(message).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn handle(
+    state: &AppState,
+    user_id: u64,
+    subscriptions: &mut HashSet<u64>,
+    command: &str,
+) -> Message {
+    let command = match serde_json::from_str::<Command>(command) {
+        Ok(command) => command,
+        Err(err) => {
+            return text(&Reply::Error {
+                msg: err.to_string(),
+            });
+        }
+    };
+
+    let reply = match command {
+        Command::Subscribe { tracker_ids } => match owned(state, user_id, &tracker_ids).await {
+            Ok(true) => {
+                subscriptions.extend(&tracker_ids);
+                Reply::Subscribed { tracker_ids }
+            }
+            Ok(false) => Reply::Error {
+                msg: Error::NotFound.to_string(),
+            },
+            Err(err) => {
+                error!("Could not check tracker ownership: {err}");
+                Reply::Error {
+                    msg: "Could not subscribe, try again".into(),
+                }
+            }
+        },
+        Command::Unsubscribe { tracker_ids } => {
+            for id in &tracker_ids {
+                subscriptions.remove(id);
+            }
+            Reply::Unsubscribed { tracker_ids }
+        }
+    };
+
+    text(&reply)
+}
+
+/// Whether every one of `ids` is a tracker of the user, nothing is subscribed otherwise
+async fn owned(state: &AppState, user_id: u64, ids: &[u64]) -> Result<bool> {
+    let ids: HashSet<_> = ids.iter().copied().collect();
+
+    let count = Trackers::find()
+        .filter(trackers::Column::Id.is_in(ids.iter().copied()))
+        .filter(trackers::Column::UserId.eq(user_id))
+        .count(&state.db)
+        .await?;
+
+    Ok(count == ids.len() as u64)
+}
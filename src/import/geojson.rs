@@ -0,0 +1,127 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::timestamp;
+use crate::ingest::Fix;
+
+fn position(value: &Value) -> Option<Fix> {
+    let coordinates = value.as_array()?;
+
+    Some(Fix {
+        lon: coordinates.first()?.as_f64()?,
+        lat: coordinates.get(1)?.as_f64()?,
+        altitude: coordinates.get(2).and_then(Value::as_f64),
+        ..Default::default()
+    })
+}
+
+fn time(value: &Value) -> Option<DateTime<Utc>> {
+    match value {
+        Value::String(value) => timestamp(value),
+        Value::Number(value) => DateTime::from_timestamp_millis(value.as_i64()?),
+        _ => None,
+    }
+}
+
+/// Reads a Point feature along with the telemetry properties of an export
+fn point(coordinates: &Value, properties: &Value) -> Result<Fix, String> {
+    let mut fix = position(coordinates).ok_or("Invalid coordinates")?;
+    let number = |key: &str| properties.get(key).and_then(Value::as_f64);
+
+    fix.recorded_at = ["recorded_at", "time", "timestamp"]
+        .iter()
+        .find_map(|key| properties.get(*key).and_then(time));
+    fix.altitude = fix.altitude.or(number("altitude"));
+    fix.speed = number("speed");
+    fix.heading = number("heading");
+    fix.accuracy = number("accuracy");
+    fix.satellites = properties
+        .get("satellites")
+        .and_then(Value::as_u64)
+        .and_then(|satellites| satellites.try_into().ok());
+    fix.battery = number("battery");
+    fix.note = ["note", "name", "description"]
+        .iter()
+        .find_map(|key| properties.get(*key).and_then(Value::as_str))
+        .unwrap_or_default()
+        .to_string();
+
+    Ok(fix)
+}
+
+/// Reads the per vertex times of a line, as written by most GPX converters
+fn times(properties: &Value) -> Option<&Vec<Value>> {
+    properties
+        .get("coordTimes")
+        .or_else(|| properties.pointer("/coordinateProperties/times"))
+        .and_then(Value::as_array)
+}
+
+fn geometry(geometry: &Value, properties: &Value, fixes: &mut Vec<Fix>) -> Result<(), String> {
+    let coordinates = &geometry["coordinates"];
+
+    match geometry["type"].as_str() {
+        Some("Point") => fixes.push(point(coordinates, properties)?),
+        Some("LineString" | "MultiPoint") => {
+            // INFO: Lines without times, like the paths of an export, can't be placed in time
+            let Some(times) = times(properties) else {
+                return Ok(());
+            };
+            let positions = coordinates.as_array().ok_or("Invalid coordinates")?;
+
+            for (position, when) in positions.iter().zip(times) {
+                let mut fix = self::position(position).ok_or("Invalid coordinates")?;
+                fix.recorded_at = time(when);
+                fixes.push(fix);
+            }
+        }
+        Some("MultiLineString") => {
+            let Some(times) = times(properties) else {
+                return Ok(());
+            };
+            let lines = coordinates.as_array().ok_or("Invalid coordinates")?;
+
+            for (line, times) in lines.iter().zip(times) {
+                let positions = line.as_array().ok_or("Invalid coordinates")?;
+                let times = times.as_array().ok_or("Invalid coordinate times")?;
+
+                for (position, when) in positions.iter().zip(times) {
+                    let mut fix = self::position(position).ok_or("Invalid coordinates")?;
+                    fix.recorded_at = time(when);
+                    fixes.push(fix);
+                }
+            }
+        }
+        Some("GeometryCollection") => {
+            for child in geometry["geometries"].as_array().into_iter().flatten() {
+                self::geometry(child, properties, fixes)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Collects the positions of a FeatureCollection, a single Feature or a bare geometry
+pub fn parse(text: &str) -> Result<Vec<Fix>, String> {
+    let document: Value = serde_json::from_str(text).map_err(|err| err.to_string())?;
+    let mut fixes = Vec::new();
+
+    match document["type"].as_str() {
+        Some("FeatureCollection") => {
+            let features = document["features"]
+                .as_array()
+                .ok_or("FeatureCollection has no features")?;
+
+            for feature in features {
+                geometry(&feature["geometry"], &feature["properties"], &mut fixes)?;
+            }
+        }
+        Some("Feature") => geometry(&document["geometry"], &document["properties"], &mut fixes)?,
+        Some(_) => geometry(&document, &Value::Null, &mut fixes)?,
+        None => return Err("Document is not GeoJSON".into()),
+    }
+
+    Ok(fixes)
+}
@@ -0,0 +1,152 @@
+use quick_xml::{
+    Reader,
+    events::{BytesStart, Event},
+};
+
+use super::timestamp;
+use crate::ingest::Fix;
+
+const POINTS: [&[u8]; 3] = [b"trkpt", b"rtept", b"wpt"];
+
+/// Reads the required `lat` and `lon` attributes of a point
+fn point(e: &BytesStart) -> Result<Fix, String> {
+    let (mut lat, mut lon) = (None, None);
+
+    for attr in e.attributes() {
+        let attr = attr.map_err(|err| err.to_string())?;
+        let value = attr.unescape_value().map_err(|err| err.to_string())?;
+
+        match attr.key.local_name().as_ref() {
+            b"lat" => lat = Some(value.trim().parse().map_err(|_| "Invalid point latitude")?),
+            b"lon" => {
+                lon = Some(
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| "Invalid point longitude")?,
+                )
+            }
+            _ => {}
+        }
+    }
+
+    let (Some(lat), Some(lon)) = (lat, lon) else {
+        return Err("Missing point latitude/longitude".into());
+    };
+
+    Ok(Fix {
+        lat,
+        lon,
+        ..Default::default()
+    })
+}
+
+/// Collects the track, route and waypoints of a GPX 1.0 or 1.1 document
+pub fn parse(text: &str) -> Result<Vec<Fix>, String> {
+    let mut reader = Reader::from_str(text);
+    let mut fixes = Vec::new();
+    let mut current: Option<Fix> = None;
+    let mut element = Vec::new();
+
+    loop {
+        match reader.read_event().map_err(|err| err.to_string())? {
+            Event::Start(e) if POINTS.contains(&e.local_name().as_ref()) => {
+                current = Some(point(&e)?);
+            }
+            Event::Start(e) => element = e.local_name().as_ref().to_vec(),
+            // INFO: A point without children has no time and gets reported as skipped
+            Event::Empty(e) if POINTS.contains(&e.local_name().as_ref()) => {
+                fixes.push(point(&e)?);
+            }
+            Event::Text(e) => {
+                let Some(fix) = current.as_mut() else {
+                    continue;
+                };
+                let value = e.unescape().map_err(|err| err.to_string())?;
+                let value = value.trim();
+
+                match element.as_slice() {
+                    b"ele" => fix.altitude = value.parse().ok(),
+                    b"time" => fix.recorded_at = timestamp(value),
+                    b"sat" => fix.satellites = value.parse().ok(),
+                    b"speed" => fix.speed = value.parse().ok(),
+                    b"course" => fix.heading = value.parse().ok(),
+                    b"name" | b"desc" | b"cmt" if fix.note.is_empty() => {
+                        fix.note = value.to_string();
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(e) if POINTS.contains(&e.local_name().as_ref()) => {
+                fixes.extend(current.take());
+            }
+            Event::End(_) => element.clear(),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(fixes)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    fn document(points: &str) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<gpx version="1.1" creator="test" xmlns="http://www.topografix.com/GPX/1/1">
+<trk><trkseg>{points}</trkseg></trk>
+</gpx>"#
+        )
+    }
+
+    #[test]
+    fn reads_track_points() {
+        let fixes = parse(&document(
+            r#"<trkpt lat="52.52" lon="13.405"><ele>34.5</ele><time>2024-05-01T12:00:00Z</time><sat>7</sat><desc>Start</desc></trkpt>
+<trkpt lat="-33.8688" lon="151.2093"/>"#,
+        ))
+        .unwrap();
+
+        assert_eq!(fixes.len(), 2);
+        assert_eq!((fixes[0].lat, fixes[0].lon), (52.52, 13.405));
+        assert_eq!(fixes[0].altitude, Some(34.5));
+        assert_eq!(
+            fixes[0].recorded_at,
+            Some(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap())
+        );
+        assert_eq!(fixes[0].satellites, Some(7));
+        assert_eq!(fixes[0].note, "Start");
+        assert_eq!((fixes[1].lat, fixes[1].lon), (-33.8688, 151.2093));
+        assert_eq!(fixes[1].recorded_at, None);
+    }
+
+    #[test]
+    fn rejects_points_missing_a_coordinate() {
+        for point in [
+            r#"<trkpt lat="52.52"><time>2024-05-01T12:00:00Z</time></trkpt>"#,
+            r#"<trkpt lon="13.405"><time>2024-05-01T12:00:00Z</time></trkpt>"#,
+            r#"<trkpt/>"#,
+        ] {
+            assert_eq!(
+                parse(&document(point)).err().as_deref(),
+                Some("Missing point latitude/longitude"),
+                "{point}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unparsable_coordinates() {
+        assert_eq!(
+            parse(&document(r#"<trkpt lat="north" lon="13.405"/>"#))
+                .err()
+                .as_deref(),
+            Some("Invalid point latitude")
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use quick_xml::{Reader, events::Event};
+
+use super::timestamp;
+use crate::ingest::Fix;
+
+#[derive(Default)]
+struct Placemark {
+    note: String,
+    when: Option<DateTime<Utc>>,
+    point: Option<Fix>,
+    track: Vec<Option<DateTime<Utc>>>,
+    coords: Vec<Fix>,
+}
+
+/// Parses `lon,lat[,alt]` or the space separated `lon lat [alt]` of a `gx:coord`
+fn coordinates(value: &str) -> Result<Fix, String> {
+    let mut parts = value
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(str::parse::<f64>);
+
+    let mut next = || parts.next().transpose().map_err(|_| "Invalid coordinates");
+    let (Some(lon), Some(lat)) = (next()?, next()?) else {
+        return Err("Invalid coordinates".into());
+    };
+
+    Ok(Fix {
+        lat,
+        lon,
+        altitude: next()?,
+        ..Default::default()
+    })
+}
+
+/// Collects timestamped Point placemarks and `gx:Track` samples of a KML 2.2 document.
+/// Plain LineStrings carry no times and are ignored.
+pub fn parse(text: &str) -> Result<Vec<Fix>, String> {
+    let mut reader = Reader::from_str(text);
+    let mut fixes = Vec::new();
+    let mut path: Vec<Vec<u8>> = Vec::new();
+    let mut placemark: Option<Placemark> = None;
+
+    loop {
+        match reader.read_event().map_err(|err| err.to_string())? {
+            Event::Start(e) => {
+                let name = e.local_name().as_ref().to_vec();
+                if name == b"Placemark" {
+                    placemark = Some(Placemark::default());
+                }
+                path.push(name);
+            }
+            Event::Text(e) => {
+                let Some(placemark) = placemark.as_mut() else {
+                    continue;
+                };
+                let value = e.unescape().map_err(|err| err.to_string())?;
+                let value = value.trim();
+
+                let (parent, element) = match path.as_slice() {
+                    [.., parent, element] => (parent.as_slice(), element.as_slice()),
+                    _ => continue,
+                };
+
+                match (parent, element) {
+                    (b"Placemark", b"name" | b"description") if placemark.note.is_empty() => {
+                        placemark.note = value.to_string();
+                    }
+                    (b"TimeStamp", b"when") | (b"TimeSpan", b"begin") => {
+                        placemark.when = timestamp(value);
+                    }
+                    (b"Point", b"coordinates") => placemark.point = Some(coordinates(value)?),
+                    (b"Track", b"when") => placemark.track.push(timestamp(value)),
+                    (b"Track", b"coord") => placemark.coords.push(coordinates(value)?),
+                    _ => {}
+                }
+            }
+            Event::End(e) => {
+                path.pop();
+
+                if e.local_name().as_ref() != b"Placemark" {
+                    continue;
+                }
+                let Some(placemark) = placemark.take() else {
+                    continue;
+                };
+
+                if let Some(mut fix) = placemark.point {
+                    fix.recorded_at = placemark.when;
+                    fix.note = placemark.note.clone();
+                    fixes.push(fix);
+                }
+
+                for (when, mut fix) in placemark.track.into_iter().zip(placemark.coords) {
+                    fix.recorded_at = when;
+                    fixes.push(fix);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(fixes)
+}
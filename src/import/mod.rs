@@ -0,0 +1,42 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::ingest::Fix;
+
+pub mod geojson;
+pub mod gpx;
+pub mod kml;
+pub mod takeout;
+
+/// Reads the fixes of a GPX, KML or GeoJSON document. The format comes from the file
+/// extension when there is one and is sniffed from the content otherwise.
+pub fn parse(filename: Option<&str>, bytes: &[u8]) -> Result<Vec<Fix>, String> {
+    let text = std::str::from_utf8(bytes).map_err(|_| "File is not valid UTF-8".to_string())?;
+    let text = text.trim_start_matches('\u{feff}');
+
+    let extension = filename
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("gpx") => gpx::parse(text),
+        Some("kml") => kml::parse(text),
+        Some("geojson" | "json") => geojson::parse(text),
+        _ if text.trim_start().starts_with('{') => geojson::parse(text),
+        _ if text.contains("<gpx") => gpx::parse(text),
+        _ if text.contains("<kml") => kml::parse(text),
+        _ => Err("Unsupported file format, expected GPX, KML or GeoJSON".into()),
+    }
+}
+
+/// Parses the timestamps found in track files, which are RFC 3339 but often lack the offset
+pub fn timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+
+    DateTime::parse_from_rfc3339(value)
+        .or_else(|_| DateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").map(|t| t.and_utc())
+        })
+        .ok()
+}
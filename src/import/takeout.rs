@@ -0,0 +1,352 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, QueryFilter, sea_query::Expr,
+};
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde_json::Value;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::sync::mpsc;
+use tracing::error;
+
+use super::timestamp;
+use crate::{
+    Error, Result,
+    entity::{import_jobs, prelude::ImportJobs, sea_orm_active_enums::Status, trackers},
+    ingest::{self, Fix},
+};
+
+/// Fixes handed to [`ingest::import`] at a time, also how often progress is saved
+const BATCH_SIZE: usize = 5000;
+
+type Emit<'a> = &'a mut dyn FnMut(Fix) -> std::result::Result<(), String>;
+
+/// Converts E7 coordinates, some older exports store them as unsigned 32 bit integers
+fn e7(value: &Value) -> Option<f64> {
+    let value = value.as_i64()?;
+    let value = if value > 1_800_000_000 {
+        value - (1 << 32)
+    } else {
+        value
+    };
+
+    Some(value as f64 / 1e7)
+}
+
+/// Parses the `"48.1371°, 11.5754°"` positions of `Timeline.json`
+fn lat_lng(value: &Value) -> Option<(f64, f64)> {
+    let (lat, lon) = value.as_str()?.trim_start_matches("geo:").split_once(',')?;
+
+    Some((
+        lat.trim().trim_end_matches('°').parse().ok()?,
+        lon.trim().trim_end_matches('°').parse().ok()?,
+    ))
+}
+
+fn fix(position: (f64, f64), recorded_at: Option<DateTime<Utc>>, note: &str) -> Fix {
+    Fix {
+        lat: position.0,
+        lon: position.1,
+        note: note.to_lowercase(),
+        recorded_at,
+        ..Default::default()
+    }
+}
+
+/// An entry of the `locations` array of `Records.json`
+fn record(value: &Value, emit: Emit) -> std::result::Result<(), String> {
+    let (Some(lat), Some(lon)) = (e7(&value["latitudeE7"]), e7(&value["longitudeE7"])) else {
+        return Ok(());
+    };
+
+    let recorded_at = match (&value["timestamp"], &value["timestampMs"]) {
+        (Value::String(t), _) => timestamp(t),
+        (_, Value::String(ms)) => ms.parse().ok().and_then(DateTime::from_timestamp_millis),
+        _ => None,
+    };
+
+    let activity = value
+        .pointer("/activity/0/activity/0/type")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    emit(Fix {
+        altitude: value["altitude"].as_f64(),
+        speed: value["velocity"].as_f64(),
+        heading: value["heading"].as_f64(),
+        accuracy: value["accuracy"].as_f64(),
+        ..fix((lat, lon), recorded_at, activity)
+    })
+}
+
+/// An entry of the `semanticSegments` array of `Timeline.json`, a visit, an activity or a path
+fn segment(value: &Value, emit: Emit) -> std::result::Result<(), String> {
+    let start = value["startTime"].as_str().and_then(timestamp);
+    let end = value["endTime"].as_str().and_then(timestamp);
+
+    for point in value["timelinePath"].as_array().into_iter().flatten() {
+        if let Some(position) = lat_lng(&point["point"]) {
+            emit(fix(
+                position,
+                point["time"].as_str().and_then(timestamp),
+                "",
+            ))?;
+        }
+    }
+
+    let visit = &value["visit"]["topCandidate"];
+    if let Some(position) = lat_lng(&visit["placeLocation"]["latLng"]) {
+        let note = visit["semanticType"].as_str().unwrap_or("visit");
+        emit(fix(position, start, note))?;
+    }
+
+    let activity = &value["activity"];
+    let note = activity["topCandidate"]["type"]
+        .as_str()
+        .unwrap_or_default();
+    if let Some(position) = lat_lng(&activity["start"]["latLng"]) {
+        emit(fix(position, start, note))?;
+    }
+    if let Some(position) = lat_lng(&activity["end"]["latLng"]) {
+        emit(fix(position, end, note))?;
+    }
+
+    Ok(())
+}
+
+/// An entry of the `rawSignals` array of `Timeline.json`, only positions are of interest
+fn signal(value: &Value, emit: Emit) -> std::result::Result<(), String> {
+    let position = &value["position"];
+    let Some(lat_lon) = lat_lng(&position["LatLng"]) else {
+        return Ok(());
+    };
+
+    emit(Fix {
+        altitude: position["altitudeMeters"].as_f64(),
+        speed: position["speedMetersPerSecond"].as_f64(),
+        accuracy: position["accuracyMeters"].as_f64(),
+        ..fix(
+            lat_lon,
+            position["timestamp"].as_str().and_then(timestamp),
+            "",
+        )
+    })
+}
+
+/// Visits the elements of an array one at a time instead of loading the whole array
+struct Each<'a> {
+    emit: Emit<'a>,
+    convert: fn(&Value, Emit) -> std::result::Result<(), String>,
+}
+
+impl<'de> DeserializeSeed<'de> for Each<'_> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Each<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(value) = seq.next_element::<Value>()? {
+            (self.convert)(&value, &mut *self.emit).map_err(de::Error::custom)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The top level object of either `Records.json` or `Timeline.json`
+struct Document<'a>(Emit<'a>);
+
+impl<'de> Visitor<'de> for Document<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Google Takeout location history")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        let emit = self.0;
+
+        while let Some(key) = map.next_key::<String>()? {
+            let convert = match key.as_str() {
+                "locations" => record,
+                "semanticSegments" => segment,
+                "rawSignals" => signal,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                    continue;
+                }
+            };
+
+            map.next_value_seed(Each {
+                emit: &mut *emit,
+                convert,
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Streams the fixes of a `Records.json` or `Timeline.json` export into `emit`
+pub fn read(reader: impl Read, emit: Emit) -> std::result::Result<(), String> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+
+    de::Deserializer::deserialize_map(&mut deserializer, Document(emit))
+        .and_then(|()| deserializer.end())
+        .map_err(|err| err.to_string())
+}
+
+/// Counts the bytes read so far to report progress
+struct Progress<R> {
+    inner: R,
+    read: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for Progress<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+async fn process(
+    db: &DatabaseConnection,
+    job: &import_jobs::Model,
+    tracker: &trackers::Model,
+    path: PathBuf,
+) -> Result<()> {
+    let read = Arc::new(AtomicU64::new(0));
+    let reader = Progress {
+        inner: BufReader::new(File::open(&path)?),
+        read: read.clone(),
+    };
+
+    let (tx, mut rx) = mpsc::channel::<Vec<Fix>>(2);
+    let parser = tokio::task::spawn_blocking(move || {
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        let closed = || "Import was aborted".to_string();
+
+        self::read(reader, &mut |fix| {
+            batch.push(fix);
+            if batch.len() >= BATCH_SIZE {
+                tx.blocking_send(std::mem::take(&mut batch))
+                    .map_err(|_| closed())?;
+            }
+            Ok(())
+        })?;
+
+        if !batch.is_empty() {
+            tx.blocking_send(batch).map_err(|_| closed())?;
+        }
+
+        Ok::<_, String>(())
+    });
+
+    let mut model = job.clone().into_active_model();
+    let (mut created, mut skipped) = (0, 0);
+
+    while let Some(fixes) = rx.recv().await {
+        let (ids, skips) = ingest::import(db, tracker, fixes).await?;
+        created += ids.len() as u64;
+        skipped += skips.len() as u64;
+
+        model.processed_bytes = Set(read.load(Ordering::Relaxed));
+        model.created_count = Set(created);
+        model.skipped_count = Set(skipped);
+        model.updated_at = Set(Utc::now());
+        model = model.save(db).await?;
+    }
+
+    parser
+        .await
+        .map_err(|err| Error::Internal(err.to_string()))?
+        .map_err(Error::BadRequest)?;
+
+    if created > 0 {
+        ingest::odometer(db, tracker.id).await?;
+    }
+
+    model.status = Set(Status::Completed);
+    model.processed_bytes = Set(job.total_bytes);
+    model.updated_at = Set(Utc::now());
+    model.save(db).await?;
+
+    Ok(())
+}
+
+/// Imports the uploaded export at `path` into `tracker` and removes the file afterwards.
+/// Progress and the outcome are recorded on `job`.
+pub async fn run(
+    db: DatabaseConnection,
+    job: import_jobs::Model,
+    tracker: trackers::Model,
+    path: PathBuf,
+) {
+    let mut model = job.clone().into_active_model();
+    model.status = Set(Status::Running);
+    model.updated_at = Set(Utc::now());
+
+    let result = match model.save(&db).await {
+        Ok(_) => process(&db, &job, &tracker, path.clone()).await,
+        Err(err) => Err(err.into()),
+    };
+
+    if let Err(err) = result {
+        error!("Import job {} failed: {err}", job.id);
+
+        let message = match err {
+            Error::BadRequest(message) => message,
+            _ => "Import failed".to_string(),
+        };
+
+        let mut model = job.into_active_model();
+        model.status = Set(Status::Failed);
+        model.error = Set(Some(message));
+        model.updated_at = Set(Utc::now());
+        if let Err(err) = model.save(&db).await {
+            error!("{err}");
+        }
+    }
+
+    if let Err(err) = tokio::fs::remove_file(&path).await {
+        error!("{err}");
+    }
+}
+
+/// Jobs only run inside the server process, so any left unfinished were cut short by a restart
+pub async fn fail_interrupted(db: &DatabaseConnection) -> Result<()> {
+    ImportJobs::update_many()
+        .col_expr(import_jobs::Column::Status, Expr::value(Status::Failed))
+        .col_expr(
+            import_jobs::Column::Error,
+            Expr::value("Interrupted by a server restart"),
+        )
+        .filter(import_jobs::Column::Status.is_in([Status::Pending, Status::Running]))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
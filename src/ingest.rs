@@ -0,0 +1,498 @@
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, EntityTrait, IntoActiveModel, QueryFilter, QuerySelect, Statement,
+    TransactionTrait,
+};
+use std::collections::{HashMap, HashSet};
+use tracing::error;
+
+use crate::{
+    AppState, Result,
+    alerts::rules::{self, Triggered},
+    entity::{
+        pings,
+        prelude::{Pings, Trackers},
+        trackers,
+    },
+    events::{self, Event},
+    geo::{self, Point},
+    geofence::{self, Crossing},
+    webhooks,
+};
+
+/// How far ahead of the server clock a device timestamp may be
+const CLOCK_SKEW_MINUTES: i64 = 10;
+
+/// Sums the haversine distance between consecutive pings of a tracker into its odometer
+const ODOMETER: &str = "UPDATE trackers SET odometer_m = (
+    SELECT COALESCE(SUM(2 * 6371008.8 * ASIN(LEAST(1, SQRT(
+        POW(SIN(RADIANS(lat - prev_lat) / 2), 2) +
+        COS(RADIANS(prev_lat)) * COS(RADIANS(lat)) * POW(SIN(RADIANS(lon - prev_lon) / 2), 2)
+    )))), 0)
+    FROM (
+        SELECT lat, lon,
+            LAG(lat) OVER w AS prev_lat,
+            LAG(lon) OVER w AS prev_lon
+        FROM pings
+        WHERE tracker_id = ?
+        WINDOW w AS (ORDER BY recorded_at, id)
+    ) legs
+) WHERE id = ?";
+
+#[derive(Debug, Default)]
+pub struct Fix {
+    pub lat: f64,
+    pub lon: f64,
+    pub note: String,
+    /// Meters above sea level
+    pub altitude: Option<f64>,
+    /// Ground speed in meters per second
+    pub speed: Option<f64>,
+    /// Course over ground in degrees clockwise from true north
+    pub heading: Option<f64>,
+    /// Horizontal accuracy radius in meters
+    pub accuracy: Option<f64>,
+    pub satellites: Option<u8>,
+    /// Battery level in percent
+    pub battery: Option<f64>,
+    /// When the device took the fix, defaults to the time it was received
+    pub recorded_at: Option<DateTime<Utc>>,
+}
+
+impl Fix {
+    /// Checks the readings of the fix on their own
+    pub fn check(&self) -> std::result::Result<(), String> {
+        // INFO: NaN slips through every comparison below and the database refuses it
+        let readings = [
+            ("Latitude", Some(self.lat)),
+            ("Longitude", Some(self.lon)),
+            ("Altitude", self.altitude),
+            ("Speed", self.speed),
+            ("Heading", self.heading),
+            ("Accuracy", self.accuracy),
+            ("Battery", self.battery),
+        ];
+
+        for (name, value) in readings {
+            if value.is_some_and(|value| !value.is_finite()) {
+                return Err(format!("{name} must be a finite number"));
+            }
+        }
+
+        if !(-90.0..=90.0).contains(&self.lat) {
+            return Err("Latitude must be between -90 and 90".into());
+        }
+
+        if !(-180.0..=180.0).contains(&self.lon) {
+            return Err("Longitude must be between -180 and 180".into());
+        }
+
+        if self.speed.is_some_and(|speed| speed < 0.0) {
+            return Err("Speed must not be negative".into());
+        }
+
+        if self
+            .heading
+            .is_some_and(|heading| !(0.0..=360.0).contains(&heading))
+        {
+            return Err("Heading must be between 0 and 360".into());
+        }
+
+        if self.accuracy.is_some_and(|accuracy| accuracy < 0.0) {
+            return Err("Accuracy must not be negative".into());
+        }
+
+        if self
+            .battery
+            .is_some_and(|battery| !(0.0..=100.0).contains(&battery))
+        {
+            return Err("Battery must be between 0 and 100".into());
+        }
+
+        if let Some(recorded_at) = self.recorded_at {
+            if recorded_at > Utc::now() + Duration::minutes(CLOCK_SKEW_MINUTES) {
+                return Err("Recorded at must not be in the future".into());
+            }
+
+            if recorded_at <= DateTime::UNIX_EPOCH {
+                return Err("Recorded at must be after 1970".into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks a live fix, which can't predate its tracker
+    pub fn validate(&self, tracker: &trackers::Model) -> std::result::Result<(), String> {
+        self.check()?;
+
+        if self
+            .recorded_at
+            .is_some_and(|recorded_at| recorded_at < tracker.created_at)
+        {
+            return Err("Recorded at must not be before the tracker was created".into());
+        }
+
+        Ok(())
+    }
+
+    fn into_active_model(self, tracker_id: u64) -> pings::ActiveModel {
+        pings::ActiveModel {
+            tracker_id: Set(tracker_id),
+            lat: Set(self.lat),
+            lon: Set(self.lon),
+            note: Set(self.note),
+            altitude: Set(self.altitude),
+            speed: Set(self.speed),
+            heading: Set(self.heading),
+            accuracy: Set(self.accuracy),
+            satellites: Set(self.satellites),
+            battery: Set(self.battery),
+            recorded_at: Set(self.recorded_at.unwrap_or_else(Utc::now)),
+            created_at: Set(Utc::now()),
+            updated_at: Set(Utc::now()),
+
+            ..Default::default()
+        }
+    }
+}
+
+/// A stored ping as far as the tracker's last position, odometer and geofences are concerned
+#[derive(Clone, Copy)]
+pub struct Leg {
+    pub id: u64,
+    pub recorded_at: DateTime<Utc>,
+    pub point: Point,
+}
+
+/// Moves the last position of the tracker along the pings newer than it, adding the distance
+/// covered to its odometer. Older pings are left to [`odometer`]. Returns where the tracker was,
+/// the legs it moved along, in order, and whether any older pings were left out.
+async fn advance(
+    txn: &DatabaseTransaction,
+    tracker_id: u64,
+    mut legs: Vec<Leg>,
+) -> Result<(Option<Point>, Vec<Leg>, bool)> {
+    // INFO: The row lock keeps concurrent requests of a tracker from advancing from the same spot
+    let Some(tracker) = Trackers::find_by_id(tracker_id)
+        .lock_exclusive()
+        .one(txn)
+        .await?
+    else {
+        return Ok((None, Vec::new(), false));
+    };
+
+    let count = legs.len();
+    legs.sort_by_key(|leg| (leg.recorded_at, leg.id));
+    if let Some(last_ping_at) = tracker.last_ping_at {
+        legs.retain(|leg| leg.recorded_at >= last_ping_at);
+    }
+    let backfilled = legs.len() < count;
+
+    let start = match (tracker.last_lat, tracker.last_lon) {
+        (Some(lat), Some(lon)) => Some(Point { lat, lon }),
+        _ => None,
+    };
+
+    let Some(latest) = legs.last().copied() else {
+        return Ok((start, legs, backfilled));
+    };
+
+    let mut previous = start;
+
+    let mut distance = 0.0;
+    for leg in &legs {
+        if let Some(previous) = previous {
+            distance += geo::haversine(previous, leg.point);
+        }
+        previous = Some(leg.point);
+    }
+
+    let mut tracker = tracker.into_active_model();
+    tracker.last_ping_id = Set(Some(latest.id));
+    tracker.last_ping_at = Set(Some(latest.recorded_at));
+    tracker.last_lat = Set(Some(latest.point.lat));
+    tracker.last_lon = Set(Some(latest.point.lon));
+    tracker.odometer_m = Set(*tracker.odometer_m.as_ref() + distance);
+    tracker.save(txn).await?;
+
+    Ok((start, legs, backfilled))
+}
+
+/// What storing a batch of pings brought about
+struct Inserted {
+    pings: Vec<pings::Model>,
+    crossings: Vec<Crossing>,
+    alerts: Vec<Triggered>,
+    /// Whether some pings predate the last one of the tracker and missed the odometer
+    backfilled: bool,
+}
+
+async fn insert(
+    txn: &DatabaseTransaction,
+    tracker_id: u64,
+    models: Vec<pings::ActiveModel>,
+) -> Result<Inserted> {
+    let mut created = Vec::with_capacity(models.len());
+
+    // WARN: One row per statement, a multi-row insert only reports its first id and the others
+    // aren't consecutive under interleaved auto-increment locking or a custom increment
+    for model in models {
+        created.push(model.insert(txn).await?);
+    }
+
+    let legs = created
+        .iter()
+        .map(|ping| Leg {
+            id: ping.id,
+            recorded_at: ping.recorded_at,
+            point: Point {
+                lat: ping.lat,
+                lon: ping.lon,
+            },
+        })
+        .collect();
+
+    let (start, legs, backfilled) = advance(txn, tracker_id, legs).await?;
+    let crossings = geofence::cross(txn, tracker_id, start, &legs).await?;
+
+    // INFO: Rules only look at pings the tracker advanced along, not at backfilled ones
+    let by_id: HashMap<_, _> = created.iter().map(|ping| (ping.id, ping)).collect();
+    let advanced: Vec<_> = legs
+        .iter()
+        .filter_map(|leg| by_id.get(&leg.id).copied())
+        .collect();
+    let alerts = rules::evaluate(txn, tracker_id, &advanced).await?;
+
+    Ok(Inserted {
+        pings: created,
+        crossings,
+        alerts,
+        backfilled,
+    })
+}
+
+/// Recomputes the odometer of a tracker over all of its pings, for when older pings were added
+pub async fn odometer<C: ConnectionTrait>(db: &C, tracker_id: u64) -> Result<()> {
+    db.execute(Statement::from_sql_and_values(
+        db.get_database_backend(),
+        ODOMETER,
+        [tracker_id.into(), tracker_id.into()],
+    ))
+    .await?;
+
+    Ok(())
+}
+
+/// Validates and inserts `fixes` for `tracker` in a single transaction, returning the ids of
+/// the created pings and the index and reason of every fix that was skipped. The created pings
+/// are published to the live streams and webhooks, and the owner is mailed about the alerts
+/// they raised. Pings older than the last one of the tracker recompute its odometer.
+pub async fn store(
+    state: &AppState,
+    tracker: &trackers::Model,
+    fixes: Vec<Fix>,
+) -> Result<(Vec<u64>, Vec<(usize, String)>)> {
+    let mut models = Vec::with_capacity(fixes.len());
+    let mut skipped = Vec::new();
+
+    for (index, fix) in fixes.into_iter().enumerate() {
+        match fix.validate(tracker) {
+            Ok(()) => models.push(fix.into_active_model(tracker.id)),
+            Err(err) => skipped.push((index, err)),
+        }
+    }
+
+    if models.is_empty() {
+        return Ok((Vec::new(), skipped));
+    }
+
+    let txn = state.db.begin().await?;
+    let inserted = insert(&txn, tracker.id, models).await?;
+
+    // INFO: A device flushing its buffer after a reconnect sends pings out of order
+    if inserted.backfilled {
+        odometer(&txn, tracker.id).await?;
+    }
+
+    let ids = inserted.pings.iter().map(|ping| ping.id).collect();
+
+    let mut happened = Vec::new();
+    happened.extend(inserted.pings.into_iter().map(|ping| Event::Ping {
+        user_id: tracker.user_id,
+        tracker_id: tracker.id,
+        ping,
+    }));
+    happened.extend(
+        inserted
+            .crossings
+            .into_iter()
+            .map(|crossing| Event::Geofence {
+                user_id: tracker.user_id,
+                tracker_id: tracker.id,
+                name: crossing.name,
+                event: crossing.event,
+            }),
+    );
+    happened.extend(inserted.alerts.iter().map(|triggered| Event::Alert {
+        user_id: tracker.user_id,
+        tracker_id: tracker.id,
+        alert: triggered.alert.clone(),
+    }));
+
+    webhooks::enqueue(&txn, &happened).await?;
+    txn.commit().await?;
+
+    for event in happened {
+        events::publish(state, event);
+    }
+
+    // INFO: The pings are stored, a failed mail shouldn't fail the request
+    if let Err(err) = rules::notify(state, tracker, inserted.alerts).await {
+        error!("Could not mail alerts of tracker {}: {err}", tracker.id);
+    }
+
+    Ok((ids, skipped))
+}
+
+/// Like [`store`] for historical fixes, which must carry a timestamp that the tracker doesn't
+/// already have a ping for. Run [`odometer`] once the import is done. Alerts raised by the
+/// import are recorded without notifying anyone.
+pub async fn import(
+    db: &DatabaseConnection,
+    tracker: &trackers::Model,
+    fixes: Vec<Fix>,
+) -> Result<(Vec<u64>, Vec<(usize, String)>)> {
+    let timestamps = fixes.iter().filter_map(|fix| fix.recorded_at);
+    let (from, to) = (timestamps.clone().min(), timestamps.max());
+
+    // INFO: Pings are stored with second precision so that is what counts as a duplicate
+    let mut seen = HashSet::new();
+    if let (Some(from), Some(to)) = (from, to) {
+        let existing: Vec<DateTime<Utc>> = Pings::find()
+            .select_only()
+            .column(pings::Column::RecordedAt)
+            .filter(pings::Column::TrackerId.eq(tracker.id))
+            .filter(pings::Column::RecordedAt.between(from, to))
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        seen.extend(existing.iter().map(DateTime::timestamp));
+    }
+
+    let mut models = Vec::with_capacity(fixes.len());
+    let mut skipped = Vec::new();
+
+    for (index, fix) in fixes.into_iter().enumerate() {
+        let Some(recorded_at) = fix.recorded_at else {
+            skipped.push((index, "Missing timestamp".into()));
+            continue;
+        };
+
+        if let Err(err) = fix.check() {
+            skipped.push((index, err));
+            continue;
+        }
+
+        if !seen.insert(recorded_at.timestamp()) {
+            skipped.push((index, "Duplicate timestamp".into()));
+            continue;
+        }
+
+        models.push(fix.into_active_model(tracker.id));
+    }
+
+    if models.is_empty() {
+        return Ok((Vec::new(), skipped));
+    }
+
+    let txn = db.begin().await?;
+    let inserted = insert(&txn, tracker.id, models).await?;
+    txn.commit().await?;
+
+    Ok((
+        inserted.pings.into_iter().map(|ping| ping.id).collect(),
+        skipped,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Puts a value into one of the readings of a fix
+    type Setter = fn(&mut Fix, f64);
+
+    fn fix() -> Fix {
+        Fix {
+            lat: 52.52,
+            lon: 13.405,
+            altitude: Some(34.0),
+            speed: Some(3.5),
+            heading: Some(90.0),
+            accuracy: Some(5.0),
+            battery: Some(80.0),
+            recorded_at: Some(Utc::now()),
+
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accepts_plausible_fix() {
+        assert_eq!(fix().check(), Ok(()));
+        assert_eq!(Fix::default().check(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_readings_that_are_not_finite() {
+        let setters: [(&str, Setter); 7] = [
+            ("Latitude", |fix, value| fix.lat = value),
+            ("Longitude", |fix, value| fix.lon = value),
+            ("Altitude", |fix, value| fix.altitude = Some(value)),
+            ("Speed", |fix, value| fix.speed = Some(value)),
+            ("Heading", |fix, value| fix.heading = Some(value)),
+            ("Accuracy", |fix, value| fix.accuracy = Some(value)),
+            ("Battery", |fix, value| fix.battery = Some(value)),
+        ];
+
+        for (name, set) in setters {
+            for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+                let mut fix = fix();
+                set(&mut fix, value);
+
+                assert_eq!(
+                    fix.check(),
+                    Err(format!("{name} must be a finite number")),
+                    "{name} {value}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_readings_out_of_range() {
+        let mut negative = fix();
+        negative.speed = Some(-1.0);
+        assert!(negative.check().is_err());
+
+        let mut future = fix();
+        future.recorded_at = Some(Utc::now() + Duration::hours(1));
+        assert!(future.check().is_err());
+    }
+
+    #[test]
+    fn rejects_non_finite_values_of_imported_points() {
+        let fixes = crate::import::gpx::parse(
+            r#"<gpx><trk><trkseg><trkpt lat="1" lon="2"><ele>inf</ele><speed>NaN</speed></trkpt></trkseg></trk></gpx>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fixes[0].check(),
+            Err("Altitude must be a finite number".into())
+        );
+    }
+}
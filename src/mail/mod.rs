@@ -1 +1,2 @@
+pub mod tracker;
 pub mod user;
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use lettre::{
+    Message, Transport,
+    message::{Mailbox, MultiPart},
+};
+use tracing::debug;
+
+use crate::{
+    Mail, Result,
+    entity::{alerts, trackers, users},
+    mail::user::HTML_TEMPLATE,
+};
+
+pub fn send_inactive(
+    mail: &Mail,
+    user: &users::Model,
+    tracker: &trackers::Model,
+    since: DateTime<Utc>,
+    link: &str,
+) -> Result<()> {
+    let user_name = user.given_name.clone();
+    let app_name = mail.from.name.clone().unwrap();
+    let message = format!(
+        "Your tracker {} has not reported since {}.",
+        tracker.name,
+        since.format("%Y-%m-%d %H:%M UTC")
+    );
+    let link_lbl = "View Tracker";
+    let subject = format!("{} stopped reporting", tracker.name);
+    let text = format!("Hi {user_name},\n{message} View it here: {link}\nCheers,\n{app_name} Team");
+
+    if cfg!(debug_assertions) {
+        debug!(text);
+        return Ok(());
+    }
+
+    let message = Message::builder()
+        .from(mail.from.clone())
+        .to(Mailbox::new(None, user.email.parse().unwrap()))
+        .subject(&subject)
+        .multipart(MultiPart::alternative_plain_html(
+            text.to_string(),
+            HTML_TEMPLATE
+                .replace("{user_name}", &user_name)
+                .replace("{app_name}", &app_name)
+                .replace("{message}", &message)
+                .replace("{subject}", &subject)
+                .replace("{link}", link)
+                .replace("{link_lbl}", link_lbl),
+        ))?;
+
+    mail.transport.send(&message)?;
+    Ok(())
+}
+
+pub fn send_recovered(
+    mail: &Mail,
+    user: &users::Model,
+    tracker: &trackers::Model,
+    link: &str,
+) -> Result<()> {
+    let user_name = user.given_name.clone();
+    let app_name = mail.from.name.clone().unwrap();
+    let message = format!("Your tracker {} is reporting again.", tracker.name);
+    let link_lbl = "View Tracker";
+    let subject = format!("{} is reporting again", tracker.name);
+    let text = format!("Hi {user_name},\n{message} View it here: {link}\nCheers,\n{app_name} Team");
+
+    if cfg!(debug_assertions) {
+        debug!(text);
+        return Ok(());
+    }
+
+    let message = Message::builder()
+        .from(mail.from.clone())
+        .to(Mailbox::new(None, user.email.parse().unwrap()))
+        .subject(&subject)
+        .multipart(MultiPart::alternative_plain_html(
+            text.to_string(),
+            HTML_TEMPLATE
+                .replace("{user_name}", &user_name)
+                .replace("{app_name}", &app_name)
+                .replace("{message}", &message)
+                .replace("{subject}", &subject)
+                .replace("{link}", link)
+                .replace("{link_lbl}", link_lbl),
+        ))?;
+
+    mail.transport.send(&message)?;
+    Ok(())
+}
+
+pub fn send_alert(
+    mail: &Mail,
+    user: &users::Model,
+    tracker: &trackers::Model,
+    alert: &alerts::Model,
+    link: &str,
+) -> Result<()> {
+    let user_name = user.given_name.clone();
+    let app_name = mail.from.name.clone().unwrap();
+    let message = format!(
+        "Your tracker {} triggered a {} alert at {}: {}.",
+        tracker.name,
+        alert.kind,
+        alert.triggered_at.format("%Y-%m-%d %H:%M UTC"),
+        alert.message
+    );
+    let link_lbl = "View Tracker";
+    let subject = format!("{}: {}", tracker.name, alert.message);
+    let text = format!("Hi {user_name},\n{message} View it here: {link}\nCheers,\n{app_name} Team");
+
+    if cfg!(debug_assertions) {
+        debug!(text);
+        return Ok(());
+    }
+
+    let message = Message::builder()
+        .from(mail.from.clone())
+        .to(Mailbox::new(None, user.email.parse().unwrap()))
+        .subject(&subject)
+        .multipart(MultiPart::alternative_plain_html(
+            text.to_string(),
+            HTML_TEMPLATE
+                .replace("{user_name}", &user_name)
+                .replace("{app_name}", &app_name)
+                .replace("{message}", &message)
+                .replace("{subject}", &subject)
+                .replace("{link}", link)
+                .replace("{link_lbl}", link_lbl),
+        ))?;
+
+    mail.transport.send(&message)?;
+    Ok(())
+}
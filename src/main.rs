@@ -13,25 +13,38 @@ use sea_orm::Database;
 use std::env;
 use std::fs;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::{Notify, broadcast};
 use tower_http::cors::CorsLayer;
-use tracing::info;
+use tracing::{error, info};
 
+mod alerts;
+mod analysis;
 mod auth;
 mod crypto;
 mod entity;
 mod error;
+mod events;
+mod export;
+mod geo;
+mod geofence;
 mod http;
+mod import;
+mod ingest;
 mod mail;
 mod response;
 mod result;
 mod skippy;
 mod state;
+mod tcp;
 mod util;
+mod webhooks;
 
-use crate::http::{DEFAULT_PORT, v1::auth::X_CSRF_TOKEN};
+use crate::http::{DEFAULT_PORT, DEV_ORIGIN, v1::auth::X_CSRF_TOKEN};
 use crate::state::AppState;
 use crate::state::Mail;
+use crate::tcp::Protocol;
 
 pub use self::error::*;
 pub use self::response::*;
@@ -57,6 +70,8 @@ async fn main() -> Result<()> {
     let db_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let db = Database::connect(db_url).await?;
 
+    import::takeout::fail_interrupted(&db).await?;
+
     let spa_url = env::var("SPA_URL").expect("SPA_URL must be set");
     let origin = spa_url.clone();
 
@@ -82,19 +97,43 @@ async fn main() -> Result<()> {
 
     let mail = Mail { transport, from };
 
+    let (events, _) = broadcast::channel(events::CAPACITY);
+
+    let allow_private_webhooks = env::var("WEBHOOKS_ALLOW_PRIVATE").is_ok_and(|s| s == "true");
+
     let state = AppState {
         db,
+        events,
+        deliveries: Arc::new(Notify::new()),
+        allow_private_webhooks,
         mail,
         prv_key,
         pub_key,
         spa_url,
     };
 
+    webhooks::spawn(state.clone());
+    alerts::inactivity::spawn(state.clone());
+
+    // INFO: Hardware trackers speak raw TCP, each protocol only listens when its port is set
+    for (key, protocol) in [("GT06_PORT", Protocol::Gt06), ("H02_PORT", Protocol::H02)] {
+        let Some(port) = env::var(key).ok().and_then(|s| s.parse::<u16>().ok()) else {
+            continue;
+        };
+
+        let state = state.clone();
+        tokio::spawn(async move {
+            if let Err(err) = tcp::serve(state, protocol, port).await {
+                error!("{protocol} listener failed: {err}");
+            }
+        });
+    }
+
     let cors = CorsLayer::new()
         .allow_origin([
             origin.parse().unwrap(),
             // INFO: This is for local development
-            "http://localhost:42069".parse().unwrap(),
+            DEV_ORIGIN.parse().unwrap(),
         ])
         .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
         .allow_headers([
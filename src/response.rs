@@ -19,6 +19,7 @@ pub enum Response {
     CreatedBatch(Vec<u64>, Vec<String>),
     Csrf(CookieJar, HeaderMap),
     NoContent,
+    Secret(String),
 }
 
 impl IntoResponse for Response {
@@ -30,6 +31,7 @@ impl IntoResponse for Response {
             Response::Created(id) => (StatusCode::CREATED, Json(id)).into_response(),
             Response::Csrf(jar, header) => (StatusCode::CREATED, jar, header).into_response(),
             Response::NoContent => StatusCode::NO_CONTENT.into_response(),
+            Response::Secret(secret) => (StatusCode::CREATED, Json(secret)).into_response(),
             Response::CreatedBatch(created_ids, skipped_records) => {
                 let response_body = BatchResult {
                     created_ids,
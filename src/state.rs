@@ -1,6 +1,10 @@
 use jsonwebtoken::{DecodingKey, EncodingKey};
 use lettre::{SmtpTransport, message::Mailbox};
 use sea_orm::DatabaseConnection;
+use std::sync::Arc;
+use tokio::sync::{Notify, broadcast};
+
+use crate::events::Event;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -12,6 +16,12 @@ pub struct AppState {
     pub pub_key: DecodingKey,
 
     pub mail: Mail,
+
+    pub events: broadcast::Sender<Event>,
+    /// Wakes the webhook dispatcher once deliveries were queued
+    pub deliveries: Arc<Notify>,
+    /// Lets webhooks target loopback and private addresses, only meant for local testing
+    pub allow_private_webhooks: bool,
 }
 
 #[derive(Clone)]
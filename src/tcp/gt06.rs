@@ -0,0 +1,322 @@
+use chrono::NaiveDate;
+
+use super::{Message, Packet};
+use crate::ingest::Fix;
+
+const LOGIN: u8 = 0x01;
+const GPS: u8 = 0x10;
+const GPS_LBS: u8 = 0x12;
+const STATUS: u8 = 0x13;
+const ALARM: u8 = 0x16;
+const GPS_LBS_EXT: u8 = 0x22;
+const HEARTBEAT: u8 = 0x23;
+const ALARM_EXT: u8 = 0x26;
+
+const START: [u8; 2] = [0x78, 0x78];
+const START_EXT: [u8; 2] = [0x79, 0x79];
+const STOP: [u8; 2] = [0x0D, 0x0A];
+
+const MPS_PER_KPH: f64 = 1.0 / 3.6;
+
+/// CRC-ITU (CRC-16/X-25) as used by the GT06 frame checksum
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in data {
+        crc ^= *byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x8408
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Drains every complete frame from `buf`, leaving any trailing partial frame in place.
+pub fn decode(buf: &mut Vec<u8>) -> Vec<Packet> {
+    let mut packets = Vec::new();
+
+    loop {
+        let Some(start) = buf.windows(2).position(|w| w == START || w == START_EXT) else {
+            let keep = usize::from(
+                buf.last()
+                    .is_some_and(|b| *b == START[0] || *b == START_EXT[0]),
+            );
+            buf.drain(..buf.len() - keep);
+            return packets;
+        };
+        buf.drain(..start);
+
+        let extended = buf[..2] == START_EXT;
+        let header = if extended { 4 } else { 3 };
+        if buf.len() < header {
+            return packets;
+        }
+
+        let length = match extended {
+            true => u16::from_be_bytes([buf[2], buf[3]]) as usize,
+            false => buf[2] as usize,
+        };
+
+        let total = header + length + STOP.len();
+        if buf.len() < total {
+            return packets;
+        }
+
+        let frame = &buf[..total];
+        let crc = u16::from_be_bytes([frame[total - 4], frame[total - 3]]);
+
+        // INFO: Skip the start bits of a corrupt frame and resync on the next one
+        if length < 5 || frame[total - 2..] != STOP || crc16(&frame[2..total - 4]) != crc {
+            buf.drain(..1);
+            continue;
+        }
+
+        if let Some(packet) = parse(&frame[header..total - 4], extended) {
+            packets.push(packet);
+        }
+
+        buf.drain(..total);
+    }
+}
+
+/// Parses the protocol number, content and serial number of a frame
+fn parse(body: &[u8], extended: bool) -> Option<Packet> {
+    let protocol = body[0];
+    let content = &body[1..body.len() - 2];
+    let serial = [body[body.len() - 2], body[body.len() - 1]];
+
+    let message = match protocol {
+        LOGIN => Message::Login(imei(content)?),
+        STATUS | HEARTBEAT => Message::Heartbeat,
+        GPS | GPS_LBS | GPS_LBS_EXT | ALARM | ALARM_EXT => match fix(content) {
+            Some(fix) => Message::Location(fix),
+            None => Message::Heartbeat,
+        },
+        _ => return None,
+    };
+
+    let ack = match protocol {
+        LOGIN | STATUS | HEARTBEAT | ALARM | ALARM_EXT => Some(ack(protocol, serial, extended)),
+        _ => None,
+    };
+
+    Some(Packet { message, ack })
+}
+
+/// The terminal id is the IMEI as 8 BCD bytes with a leading zero nibble
+fn imei(content: &[u8]) -> Option<String> {
+    let digits = hex::encode(content.get(..8)?);
+    Some(digits.strip_prefix('0').unwrap_or(&digits).to_string())
+}
+
+fn fix(content: &[u8]) -> Option<Fix> {
+    let content = content.get(..18)?;
+
+    let recorded_at = NaiveDate::from_ymd_opt(
+        2000 + content[0] as i32,
+        content[1] as u32,
+        content[2] as u32,
+    )?
+    .and_hms_opt(content[3] as u32, content[4] as u32, content[5] as u32)?
+    .and_utc();
+
+    let satellites = content[6] & 0x0F;
+    let mut lat = u32::from_be_bytes([content[7], content[8], content[9], content[10]]) as f64;
+    let mut lon = u32::from_be_bytes([content[11], content[12], content[13], content[14]]) as f64;
+    let speed = content[15] as f64 * MPS_PER_KPH;
+    let flags = u16::from_be_bytes([content[16], content[17]]);
+
+    // INFO: Bit 12 is set once the GPS has a position, without it the coordinates are stale
+    if flags & (1 << 12) == 0 {
+        return None;
+    }
+
+    // INFO: Coordinates are in units of 1/30000 of a minute
+    lat /= 1_800_000.0;
+    lon /= 1_800_000.0;
+
+    if flags & (1 << 10) == 0 {
+        lat = -lat;
+    }
+
+    if flags & (1 << 11) != 0 {
+        lon = -lon;
+    }
+
+    Some(Fix {
+        lat,
+        lon,
+        speed: Some(speed),
+        heading: Some((flags & 0x03FF) as f64),
+        satellites: Some(satellites),
+        recorded_at: Some(recorded_at),
+
+        ..Default::default()
+    })
+}
+
+fn ack(protocol: u8, serial: [u8; 2], extended: bool) -> Vec<u8> {
+    let mut frame = match extended {
+        true => vec![START_EXT[0], START_EXT[1], 0x00, 0x05],
+        false => vec![START[0], START[1], 0x05],
+    };
+
+    frame.push(protocol);
+    frame.extend_from_slice(&serial);
+
+    let crc = crc16(&frame[2..]);
+    frame.extend_from_slice(&crc.to_be_bytes());
+    frame.extend_from_slice(&STOP);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    // INFO: Sample frames from the GT06 protocol manual
+    const LOGIN_FRAME: &str = "78780d01012345678901234500018cdd0d0a";
+    const STATUS_FRAME: &str = "78780a134004040001000fdcee0d0a";
+    const GPS_LBS_FRAME: &str =
+        "78781f120b081d112e10cf027ac7eb0c46584900148f01cc00287d001fb8000380810d0a";
+    const GPS_LBS_EXT_FRAME: &str =
+        "787822220f0c1d023305c9027ac8180c46586000140001cc00287d001f71000001000820860d0a";
+
+    fn bytes(frames: &[&str]) -> Vec<u8> {
+        frames
+            .iter()
+            .flat_map(|frame| hex::decode(frame).unwrap())
+            .collect()
+    }
+
+    fn location(packet: &Packet) -> &Fix {
+        match &packet.message {
+            Message::Location(fix) => fix,
+            message => panic!("Expected a location, got {message:?}"),
+        }
+    }
+
+    #[test]
+    fn decodes_login_and_acks_it() {
+        let mut buf = bytes(&[LOGIN_FRAME]);
+        let packets = decode(&mut buf);
+
+        assert_eq!(packets.len(), 1);
+        assert!(matches!(&packets[0].message, Message::Login(imei) if imei == "123456789012345"));
+        assert_eq!(
+            packets[0].ack,
+            Some(hex::decode("787805010001d9dc0d0a").unwrap())
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn decodes_status_as_heartbeat_and_acks_it() {
+        let packets = decode(&mut bytes(&[STATUS_FRAME]));
+
+        assert_eq!(packets.len(), 1);
+        assert!(matches!(packets[0].message, Message::Heartbeat));
+        assert_eq!(
+            packets[0].ack,
+            Some(hex::decode("78780513000f008f0d0a").unwrap())
+        );
+    }
+
+    #[test]
+    fn decodes_gps_lbs_location() {
+        let packets = decode(&mut bytes(&[GPS_LBS_FRAME]));
+
+        assert_eq!(packets.len(), 1);
+        assert!(packets[0].ack.is_none());
+
+        let fix = location(&packets[0]);
+        assert!((fix.lat - 23.111_668).abs() < 1e-6);
+        assert!((fix.lon - 114.409_285).abs() < 1e-6);
+        assert_eq!(fix.satellites, Some(15));
+        assert_eq!(fix.speed, Some(0.0));
+        assert_eq!(fix.heading, Some(143.0));
+        assert_eq!(
+            fix.recorded_at,
+            Some(Utc.with_ymd_and_hms(2011, 8, 29, 17, 46, 16).unwrap())
+        );
+    }
+
+    #[test]
+    fn decodes_extended_gps_lbs_location() {
+        let packets = decode(&mut bytes(&[GPS_LBS_EXT_FRAME]));
+
+        assert_eq!(packets.len(), 1);
+        assert!(packets[0].ack.is_none());
+
+        let fix = location(&packets[0]);
+        assert!((fix.lat - 23.111_693).abs() < 1e-6);
+        assert!((fix.lon - 114.409_298).abs() < 1e-6);
+        assert_eq!(fix.satellites, Some(9));
+        assert_eq!(fix.heading, Some(0.0));
+        assert_eq!(
+            fix.recorded_at,
+            Some(Utc.with_ymd_and_hms(2015, 12, 29, 2, 51, 5).unwrap())
+        );
+    }
+
+    #[test]
+    fn keeps_partial_frame_until_it_completes() {
+        let frame = bytes(&[GPS_LBS_FRAME]);
+        let (head, tail) = frame.split_at(20);
+
+        let mut buf = head.to_vec();
+        assert!(decode(&mut buf).is_empty());
+        assert_eq!(buf, head);
+
+        buf.extend_from_slice(tail);
+        let packets = decode(&mut buf);
+        assert_eq!(packets.len(), 1);
+        assert!(matches!(packets[0].message, Message::Location(_)));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn keeps_a_trailing_start_byte() {
+        let mut buf = vec![0x00, 0x01, 0x78];
+        assert!(decode(&mut buf).is_empty());
+        assert_eq!(buf, [0x78]);
+    }
+
+    #[test]
+    fn resyncs_after_garbage_and_corrupt_frames() {
+        let mut corrupt = hex::decode(GPS_LBS_FRAME).unwrap();
+        let crc = corrupt.len() - 4;
+        corrupt[crc] ^= 0xFF;
+
+        let mut buf = vec![0xDE, 0xAD];
+        buf.extend(corrupt);
+        buf.extend(bytes(&[LOGIN_FRAME, GPS_LBS_EXT_FRAME]));
+
+        let packets = decode(&mut buf);
+
+        assert_eq!(packets.len(), 2);
+        assert!(matches!(&packets[0].message, Message::Login(imei) if imei == "123456789012345"));
+        assert!(matches!(packets[1].message, Message::Location(_)));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn resyncs_after_a_stray_start_byte_once_enough_bytes_arrived() {
+        // INFO: The stray byte makes `78 78 78` read as a frame of 0x78 bytes that fails its CRC
+        let mut buf = vec![0x78];
+        buf.extend(bytes(&[LOGIN_FRAME]));
+        assert!(decode(&mut buf).is_empty());
+
+        buf.extend(bytes(&[GPS_LBS_FRAME; 4]));
+        let packets = decode(&mut buf);
+
+        assert_eq!(packets.len(), 5);
+        assert!(matches!(&packets[0].message, Message::Login(imei) if imei == "123456789012345"));
+        assert!(buf.is_empty());
+    }
+}
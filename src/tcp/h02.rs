@@ -0,0 +1,177 @@
+use chrono::{NaiveDate, NaiveTime};
+
+use super::{Message, Packet};
+use crate::ingest::Fix;
+
+const MPS_PER_KNOT: f64 = 0.514_444;
+
+/// Drains every complete `*XX,...#` sentence from `buf`, leaving any trailing partial one.
+pub fn decode(buf: &mut Vec<u8>) -> Vec<Packet> {
+    let mut packets = Vec::new();
+
+    loop {
+        let Some(start) = buf.iter().position(|b| *b == b'*') else {
+            buf.clear();
+            return packets;
+        };
+        buf.drain(..start);
+
+        let Some(end) = buf.iter().position(|b| *b == b'#') else {
+            return packets;
+        };
+
+        let sentence: Vec<u8> = buf.drain(..=end).collect();
+        if let Ok(sentence) = std::str::from_utf8(&sentence[1..end]) {
+            packets.extend(parse(sentence));
+        }
+    }
+}
+
+/// Every sentence carries the device id, so it doubles as a login
+fn parse(sentence: &str) -> Vec<Packet> {
+    let fields: Vec<&str> = sentence.split(',').collect();
+    if fields.len() < 3 {
+        return Vec::new();
+    }
+
+    let login = Packet {
+        message: Message::Login(fields[1].to_string()),
+        ack: None,
+    };
+
+    let message = match fields[2] {
+        "V1" => fix(&fields).map_or(Message::Heartbeat, Message::Location),
+        _ => Message::Heartbeat,
+    };
+
+    vec![login, Packet { message, ack: None }]
+}
+
+/// Converts `ddmm.mmmm` / `dddmm.mmmm` with its hemisphere into decimal degrees
+fn coordinate(value: &str, hemisphere: &str) -> Option<f64> {
+    let value = value.parse::<f64>().ok()?;
+    let degrees = (value / 100.0).trunc();
+    let decimal = degrees + (value - degrees * 100.0) / 60.0;
+
+    match hemisphere {
+        "N" | "E" => Some(decimal),
+        "S" | "W" => Some(-decimal),
+        _ => None,
+    }
+}
+
+/// `*HQ,id,V1,hhmmss,A,lat,N,lon,E,speed,course,ddmmyy,status,...#`
+fn fix(fields: &[&str]) -> Option<Fix> {
+    if fields.len() < 12 || fields[4] != "A" {
+        return None;
+    }
+
+    let time = NaiveTime::parse_from_str(fields[3], "%H%M%S").ok()?;
+    let date = NaiveDate::parse_from_str(fields[11], "%d%m%y").ok()?;
+
+    Some(Fix {
+        lat: coordinate(fields[5], fields[6])?,
+        lon: coordinate(fields[7], fields[8])?,
+        speed: fields[9]
+            .parse::<f64>()
+            .ok()
+            .map(|knots| knots * MPS_PER_KNOT),
+        heading: fields[10].parse::<f64>().ok(),
+        recorded_at: Some(date.and_time(time).and_utc()),
+
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    const FIX: &str =
+        "*HQ,865205030330012,V1,145452,A,2240.55181,N,11358.32389,E,0.00,0,100815,FFFFFBFF#";
+    const FIX_SOUTH_WEST: &str = "*HQ,353588020068342,V1,062840,A,5241.1249,S,954.9490,W,10.00,90.00,231013,FFFFFBFF,000000,000000,000000,000000#";
+    const NO_FIX: &str =
+        "*HQ,865205030330012,V1,145452,V,2240.55181,N,11358.32389,E,0.00,0,100815,FFFFFBFF#";
+
+    fn location(packet: &Packet) -> &Fix {
+        match &packet.message {
+            Message::Location(fix) => fix,
+            message => panic!("Expected a location, got {message:?}"),
+        }
+    }
+
+    #[test]
+    fn decodes_v1_location() {
+        let mut buf = FIX.as_bytes().to_vec();
+        let packets = decode(&mut buf);
+
+        assert_eq!(packets.len(), 2);
+        assert!(matches!(&packets[0].message, Message::Login(imei) if imei == "865205030330012"));
+        assert!(packets.iter().all(|packet| packet.ack.is_none()));
+
+        let fix = location(&packets[1]);
+        assert!((fix.lat - 22.675_863).abs() < 1e-6);
+        assert!((fix.lon - 113.972_065).abs() < 1e-6);
+        assert_eq!(fix.speed, Some(0.0));
+        assert_eq!(fix.heading, Some(0.0));
+        assert_eq!(
+            fix.recorded_at,
+            Some(Utc.with_ymd_and_hms(2015, 8, 10, 14, 54, 52).unwrap())
+        );
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn decodes_southern_and_western_hemispheres() {
+        let packets = decode(&mut FIX_SOUTH_WEST.as_bytes().to_vec());
+
+        let fix = location(&packets[1]);
+        assert!((fix.lat + 52.685_415).abs() < 1e-6);
+        assert!((fix.lon + 9.915_817).abs() < 1e-6);
+        assert!((fix.speed.unwrap() - 5.144_44).abs() < 1e-6);
+        assert_eq!(fix.heading, Some(90.0));
+        assert_eq!(
+            fix.recorded_at,
+            Some(Utc.with_ymd_and_hms(2013, 10, 23, 6, 28, 40).unwrap())
+        );
+    }
+
+    #[test]
+    fn treats_invalid_fix_as_heartbeat() {
+        let packets = decode(&mut NO_FIX.as_bytes().to_vec());
+
+        assert_eq!(packets.len(), 2);
+        assert!(matches!(packets[1].message, Message::Heartbeat));
+    }
+
+    #[test]
+    fn keeps_partial_sentence_until_it_completes() {
+        let (head, tail) = FIX.split_at(40);
+
+        let mut buf = head.as_bytes().to_vec();
+        assert!(decode(&mut buf).is_empty());
+        assert_eq!(buf, head.as_bytes());
+
+        buf.extend_from_slice(tail.as_bytes());
+        let packets = decode(&mut buf);
+        assert_eq!(packets.len(), 2);
+        assert!(matches!(packets[1].message, Message::Location(_)));
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn resyncs_after_garbage_and_corrupt_sentences() {
+        let mut buf = b"\r\nnoise".to_vec();
+        buf.extend_from_slice(b"*HQ,\xFF\xFE,V1#");
+        buf.extend_from_slice(b"*HQ#");
+        buf.extend_from_slice(FIX.as_bytes());
+
+        let packets = decode(&mut buf);
+
+        assert_eq!(packets.len(), 2);
+        assert!(matches!(packets[1].message, Message::Location(_)));
+        assert!(buf.is_empty());
+    }
+}
@@ -0,0 +1,142 @@
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    AppState, Result,
+    entity::{prelude::Trackers, trackers},
+    ingest::{self, Fix},
+};
+
+pub mod gt06;
+pub mod h02;
+
+const IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+const MAX_BUFFER: usize = 4096;
+/// How long to back off after a failed accept, like when out of file descriptors
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug)]
+pub enum Protocol {
+    Gt06,
+    H02,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gt06 => write!(f, "GT06"),
+            Self::H02 => write!(f, "H02"),
+        }
+    }
+}
+
+impl Protocol {
+    fn decode(&self, buf: &mut Vec<u8>) -> Vec<Packet> {
+        match self {
+            Self::Gt06 => gt06::decode(buf),
+            Self::H02 => h02::decode(buf),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Message {
+    Login(String),
+    Heartbeat,
+    Location(Fix),
+}
+
+#[derive(Debug)]
+pub struct Packet {
+    pub message: Message,
+    pub ack: Option<Vec<u8>>,
+}
+
+pub async fn serve(state: AppState, protocol: Protocol, port: u16) -> Result<()> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let listener = TcpListener::bind(addr).await?;
+    info!(
+        "Listening for {protocol} devices on tcp://{}",
+        listener.local_addr()?
+    );
+
+    loop {
+        // INFO: Accept errors concern a single connection or pass, the listener stays up
+        let (stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                error!("Could not accept {protocol} connection: {err}");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        let state = state.clone();
+
+        tokio::spawn(async move {
+            if let Err(err) = handle(&state, protocol, stream).await {
+                warn!("{protocol} connection from {peer} failed: {err}");
+            }
+        });
+    }
+}
+
+async fn handle(state: &AppState, protocol: Protocol, mut stream: TcpStream) -> Result<()> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    let mut tracker: Option<trackers::Model> = None;
+
+    loop {
+        let read = match tokio::time::timeout(IDLE_TIMEOUT, stream.read(&mut chunk)).await {
+            Ok(read) => read?,
+            Err(_) => return Ok(()),
+        };
+
+        if read == 0 {
+            return Ok(());
+        }
+
+        buf.extend_from_slice(&chunk[..read]);
+
+        for packet in protocol.decode(&mut buf) {
+            match packet.message {
+                Message::Login(imei) => {
+                    if tracker.as_ref().and_then(|t| t.imei.as_deref()) != Some(imei.as_str()) {
+                        tracker = Trackers::find()
+                            .filter(trackers::Column::Imei.eq(&imei))
+                            .one(&state.db)
+                            .await?;
+                    }
+
+                    if tracker.is_none() {
+                        debug!("Unknown {protocol} device {imei}");
+                    }
+                }
+                Message::Heartbeat => {}
+                Message::Location(fix) => {
+                    let Some(tracker) = &tracker else {
+                        continue;
+                    };
+
+                    let (_, skipped) = ingest::store(state, tracker, vec![fix]).await?;
+                    for (_, err) in skipped {
+                        debug!("Skipping {protocol} fix for tracker {}: {err}", tracker.id);
+                    }
+                }
+            }
+
+            // INFO: Unknown devices are left unacknowledged so they keep retrying the login
+            if let (Some(ack), Some(_)) = (packet.ack, &tracker) {
+                stream.write_all(&ack).await?;
+            }
+        }
+
+        if buf.len() > MAX_BUFFER {
+            buf.clear();
+        }
+    }
+}
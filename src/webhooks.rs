@@ -0,0 +1,633 @@
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use reqwest::{
+    Client,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header,
+};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DatabaseConnection,
+    EntityTrait, IntoActiveModel, QueryFilter, QueryOrder, QuerySelect, sea_query::Expr,
+};
+use serde::Serialize;
+use sha2::Sha256;
+use std::{
+    collections::HashSet,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+use tokio::sync::Notify;
+use tracing::{error, warn};
+use url::{Host, Url};
+
+use crate::{
+    AppState, Result,
+    entity::{
+        prelude::{WebhookDeliveries, Webhooks},
+        sea_orm_active_enums::DeliveryStatus,
+        webhook_deliveries, webhooks,
+    },
+    events::Event,
+};
+
+/// Attempts of a delivery before it is given up on
+const MAX_ATTEMPTS: u32 = 8;
+
+/// Failed attempts in a row, over all deliveries, after which a webhook is disabled
+const MAX_FAILURES: u32 = 20;
+
+/// Delay before the first retry, doubled on every one after it
+const BACKOFF_SECONDS: i64 = 30;
+
+/// How often due retries are looked for when no new event wakes the dispatcher
+const POLL_SECONDS: u64 = 5;
+
+const TIMEOUT_SECONDS: u64 = 10;
+
+/// Deliveries picked up per round
+const BATCH_SIZE: u64 = 100;
+
+/// Deliveries in flight at the same time
+const CONCURRENCY: usize = 8;
+
+/// Rows per insert statement, keeps large ping batches under the MySQL placeholder limit
+const CHUNK_SIZE: usize = 1000;
+
+pub const EVENT_HEADER: &str = "X-Dracker-Event";
+pub const DELIVERY_HEADER: &str = "X-Dracker-Delivery";
+pub const SIGNATURE_HEADER: &str = "X-Dracker-Signature";
+
+#[derive(Serialize)]
+struct Payload<'a, T: Serialize> {
+    event: &'a str,
+    created_at: DateTime<Utc>,
+    data: &'a T,
+}
+
+/// `sha256=` and the hex HMAC-SHA256 of `body` keyed with the webhook secret
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Starts sending queued deliveries, woken by [`crate::events::publish`]
+pub fn spawn(state: AppState) {
+    let client = client(state.allow_private_webhooks);
+
+    tokio::spawn(dispatch(
+        state.db,
+        client,
+        state.allow_private_webhooks,
+        state.deliveries,
+    ));
+}
+
+fn client(allow_private: bool) -> Client {
+    let mut builder = Client::builder()
+        .timeout(std::time::Duration::from_secs(TIMEOUT_SECONDS))
+        .redirect(reqwest::redirect::Policy::none())
+        .user_agent("dracker-webhooks");
+
+    // INFO: Resolving at send time too keeps a host from turning private after it was registered
+    if !allow_private {
+        builder = builder.dns_resolver(Arc::new(PublicResolver));
+    }
+
+    builder.build().expect("Could not build the webhook client")
+}
+
+/// Whether webhooks may be sent to `ip`, which rules out loopback, link-local, private and other
+/// special-purpose addresses
+pub fn public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // INFO: Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => public(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Checks that `url` points at a public host, resolving it when it is a name
+pub async fn check_host(url: &Url) -> std::result::Result<(), String> {
+    let ips: Vec<IpAddr> = match url.host() {
+        Some(Host::Ipv4(ip)) => vec![ip.into()],
+        Some(Host::Ipv6(ip)) => vec![ip.into()],
+        Some(Host::Domain(domain)) => tokio::net::lookup_host((domain, 0))
+            .await
+            .map_err(|_| format!("Could not resolve {domain}"))?
+            .map(|addr| addr.ip())
+            .collect(),
+        None => return Err("url must have a host".into()),
+    };
+
+    if ips.is_empty() || !ips.into_iter().all(public) {
+        return Err("url must point at a public address".into());
+    }
+
+    Ok(())
+}
+
+/// Only hands out the public addresses of a host, so a name can't lead to an internal service
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| public(addr.ip()))
+                .collect();
+
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// What a webhook subscribed to, parsed once per [`enqueue`]
+struct Subscription {
+    id: u64,
+    user_id: u64,
+    events: Vec<String>,
+    /// All trackers when absent
+    tracker_ids: Option<Vec<u64>>,
+}
+
+impl From<webhooks::Model> for Subscription {
+    fn from(hook: webhooks::Model) -> Self {
+        Subscription {
+            id: hook.id,
+            user_id: hook.user_id,
+            events: serde_json::from_value(hook.events).unwrap_or_default(),
+            tracker_ids: hook
+                .tracker_ids
+                .map(|ids| serde_json::from_value(ids).unwrap_or_default()),
+        }
+    }
+}
+
+impl Subscription {
+    fn wants(&self, event: &Event) -> bool {
+        self.user_id == event.user_id()
+            && self.events.iter().any(|name| name == event.name())
+            && self
+                .tracker_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&event.tracker_id()))
+    }
+}
+
+/// Queues a delivery of every event to each enabled webhook of its owner that subscribed to it
+/// and, when it filters by tracker, to its tracker. Run it in the transaction that stores what
+/// the events are about, so they are delivered if and only if that is committed. Returns how
+/// many were queued.
+pub async fn enqueue<C: ConnectionTrait>(db: &C, events: &[Event]) -> Result<usize> {
+    let user_ids: HashSet<_> = events.iter().map(Event::user_id).collect();
+    if user_ids.is_empty() {
+        return Ok(0);
+    }
+
+    let hooks: Vec<Subscription> = Webhooks::find()
+        .filter(webhooks::Column::UserId.is_in(user_ids))
+        .filter(webhooks::Column::Enabled.eq(true))
+        .all(db)
+        .await?
+        .into_iter()
+        .map(Subscription::from)
+        .collect();
+
+    if hooks.is_empty() {
+        return Ok(0);
+    }
+
+    let mut deliveries = Vec::new();
+
+    for event in events {
+        let mut hooks = hooks.iter().filter(|hook| hook.wants(event)).peekable();
+        if hooks.peek().is_none() {
+            continue;
+        }
+
+        let payload = payload(event.name(), event)?;
+        deliveries.extend(hooks.map(|hook| delivery(hook.id, event.name(), payload.clone())));
+    }
+
+    insert(db, deliveries).await
+}
+
+/// Queues a delivery of `data` to each of `hooks`, whatever they subscribed to
+pub async fn enqueue_to<C: ConnectionTrait, T: Serialize>(
+    db: &C,
+    hooks: &[webhooks::Model],
+    name: &str,
+    data: &T,
+) -> Result<usize> {
+    let payload = payload(name, data)?;
+    let deliveries = hooks
+        .iter()
+        .map(|hook| delivery(hook.id, name, payload.clone()))
+        .collect();
+
+    insert(db, deliveries).await
+}
+
+fn payload<T: Serialize>(name: &str, data: &T) -> Result<String> {
+    serde_json::to_string(&Payload {
+        event: name,
+        created_at: Utc::now(),
+        data,
+    })
+    .map_err(|err| crate::Error::Internal(err.to_string()))
+}
+
+fn delivery(webhook_id: u64, name: &str, payload: String) -> webhook_deliveries::ActiveModel {
+    webhook_deliveries::ActiveModel {
+        webhook_id: Set(webhook_id),
+        event: Set(name.to_string()),
+        payload: Set(payload),
+        next_attempt_at: Set(Some(Utc::now())),
+
+        ..Default::default()
+    }
+}
+
+async fn insert<C: ConnectionTrait>(
+    db: &C,
+    deliveries: Vec<webhook_deliveries::ActiveModel>,
+) -> Result<usize> {
+    let count = deliveries.len();
+    let mut deliveries = deliveries.into_iter().peekable();
+
+    while deliveries.peek().is_some() {
+        let chunk: Vec<_> = deliveries.by_ref().take(CHUNK_SIZE).collect();
+        WebhookDeliveries::insert_many(chunk).exec(db).await?;
+    }
+
+    Ok(count)
+}
+
+async fn dispatch(
+    db: DatabaseConnection,
+    client: Client,
+    allow_private: bool,
+    notify: Arc<Notify>,
+) {
+    loop {
+        tokio::select! {
+            _ = notify.notified() => {}
+            _ = tokio::time::sleep(std::time::Duration::from_secs(POLL_SECONDS)) => {}
+        }
+
+        loop {
+            match deliver_due(&db, &client, allow_private).await {
+                Ok(count) if count as u64 == BATCH_SIZE => continue,
+                Ok(_) => break,
+                Err(err) => {
+                    error!("Could not deliver webhooks: {err}");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Sends a batch of the deliveries that are due, returning how many were attempted
+async fn deliver_due(
+    db: &DatabaseConnection,
+    client: &Client,
+    allow_private: bool,
+) -> Result<usize> {
+    let due = WebhookDeliveries::find()
+        .find_also_related(Webhooks)
+        .filter(webhook_deliveries::Column::Status.eq(DeliveryStatus::Pending))
+        .filter(webhook_deliveries::Column::NextAttemptAt.lte(Utc::now()))
+        .filter(webhooks::Column::Enabled.eq(true))
+        .order_by_asc(webhook_deliveries::Column::NextAttemptAt)
+        .order_by_asc(webhook_deliveries::Column::Id)
+        .limit(BATCH_SIZE)
+        .all(db)
+        .await?;
+
+    let count = due.len();
+
+    futures::stream::iter(due)
+        .for_each_concurrent(CONCURRENCY, |(delivery, hook)| async move {
+            let Some(hook) = hook else {
+                return;
+            };
+
+            if let Err(err) = deliver(db, client, allow_private, delivery, &hook).await {
+                error!("Could not record webhook delivery: {err}");
+            }
+        })
+        .await;
+
+    Ok(count)
+}
+
+/// Posts a signed payload, returning the response status and why it failed, if it did
+async fn send(
+    client: &Client,
+    allow_private: bool,
+    url: &str,
+    secret: &str,
+    event: &str,
+    delivery_id: u64,
+    payload: &str,
+) -> (Option<u16>, Option<String>) {
+    // INFO: Addresses in the url itself never reach the resolver
+    let ip = Url::parse(url).ok().and_then(|url| match url.host() {
+        Some(Host::Ipv4(ip)) => Some(IpAddr::from(ip)),
+        Some(Host::Ipv6(ip)) => Some(IpAddr::from(ip)),
+        _ => None,
+    });
+
+    if !allow_private && ip.is_some_and(|ip| !public(ip)) {
+        return (None, Some("url does not point at a public address".into()));
+    }
+
+    let response = client
+        .post(url)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, event)
+        .header(DELIVERY_HEADER, delivery_id)
+        .header(SIGNATURE_HEADER, sign(secret, payload.as_bytes()))
+        .body(payload.to_string())
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+        Ok(response) => (
+            Some(response.status().as_u16()),
+            Some(format!("Responded with {}", response.status())),
+        ),
+        Err(err) => (None, Some(err.to_string())),
+    }
+}
+
+async fn deliver(
+    db: &DatabaseConnection,
+    client: &Client,
+    allow_private: bool,
+    delivery: webhook_deliveries::Model,
+    hook: &webhooks::Model,
+) -> Result<()> {
+    let (response_status, error) = send(
+        client,
+        allow_private,
+        &hook.url,
+        &hook.secret,
+        &delivery.event,
+        delivery.id,
+        &delivery.payload,
+    )
+    .await;
+
+    let attempts = delivery.attempts + 1;
+    let succeeded = error.is_none();
+
+    let mut delivery = delivery.into_active_model();
+    delivery.attempts = Set(attempts);
+    delivery.response_status = Set(response_status);
+    delivery.error = Set(error);
+    delivery.updated_at = Set(Utc::now());
+
+    if succeeded {
+        delivery.status = Set(DeliveryStatus::Succeeded);
+        delivery.next_attempt_at = Set(None);
+        delivery.delivered_at = Set(Some(Utc::now()));
+    } else if attempts >= MAX_ATTEMPTS {
+        delivery.status = Set(DeliveryStatus::Failed);
+        delivery.next_attempt_at = Set(None);
+    } else {
+        let backoff = Duration::seconds(BACKOFF_SECONDS << (attempts - 1));
+        delivery.next_attempt_at = Set(Some(Utc::now() + backoff));
+    }
+
+    delivery.update(db).await?;
+
+    if succeeded {
+        Webhooks::update_many()
+            .col_expr(webhooks::Column::FailureCount, Expr::value(0))
+            .filter(webhooks::Column::Id.eq(hook.id))
+            .filter(webhooks::Column::FailureCount.ne(0))
+            .exec(db)
+            .await?;
+
+        return Ok(());
+    }
+
+    Webhooks::update_many()
+        .col_expr(
+            webhooks::Column::FailureCount,
+            Expr::col(webhooks::Column::FailureCount).add(1),
+        )
+        .filter(webhooks::Column::Id.eq(hook.id))
+        .exec(db)
+        .await?;
+
+    disable(db, hook.id).await
+}
+
+/// Turns the webhook off once it failed too often in a row and gives up on what it has pending
+async fn disable(db: &DatabaseConnection, id: u64) -> Result<()> {
+    // INFO: Only the attempt that flips `enabled` gets a row back, so this runs once per outage
+    let disabled = Webhooks::update_many()
+        .col_expr(webhooks::Column::Enabled, Expr::value(false))
+        .col_expr(webhooks::Column::UpdatedAt, Expr::value(Utc::now()))
+        .filter(webhooks::Column::Id.eq(id))
+        .filter(webhooks::Column::Enabled.eq(true))
+        .filter(webhooks::Column::FailureCount.gte(MAX_FAILURES))
+        .exec(db)
+        .await?;
+
+    if disabled.rows_affected == 0 {
+        return Ok(());
+    }
+
+    warn!("Disabled webhook {id} after {MAX_FAILURES} failed attempts in a row");
+
+    WebhookDeliveries::update_many()
+        .col_expr(
+            webhook_deliveries::Column::Status,
+            Expr::value(DeliveryStatus::Failed),
+        )
+        .col_expr(
+            webhook_deliveries::Column::Error,
+            Expr::value("Webhook was disabled after repeated failures"),
+        )
+        .col_expr(
+            webhook_deliveries::Column::NextAttemptAt,
+            Expr::value(Option::<DateTime<Utc>>::None),
+        )
+        .col_expr(
+            webhook_deliveries::Column::UpdatedAt,
+            Expr::value(Utc::now()),
+        )
+        .filter(webhook_deliveries::Column::WebhookId.eq(id))
+        .filter(webhook_deliveries::Column::Status.eq(DeliveryStatus::Pending))
+        .exec(db)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Bytes, extract::State, http::HeaderMap, routing::post};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    /// An HTTP stub on a random local port that hands every request it gets to the test
+    async fn stub() -> (String, mpsc::UnboundedReceiver<(HeaderMap, Bytes)>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(
+                    |State(tx): State<mpsc::UnboundedSender<_>>,
+                     headers: HeaderMap,
+                     body: Bytes| async move {
+                        tx.send((headers, body)).unwrap();
+                    },
+                ),
+            )
+            .with_state(tx);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        (format!("127.0.0.1:{port}/hook"), rx)
+    }
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        // INFO: RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn public_rules_out_internal_addresses() {
+        for ip in [
+            IpAddr::from(Ipv4Addr::LOCALHOST),
+            IpAddr::from(Ipv4Addr::UNSPECIFIED),
+            IpAddr::from([10, 0, 0, 1]),
+            IpAddr::from([172, 16, 5, 4]),
+            IpAddr::from([192, 168, 1, 1]),
+            IpAddr::from([169, 254, 169, 254]),
+            IpAddr::from([100, 64, 0, 1]),
+            IpAddr::from(Ipv6Addr::LOCALHOST),
+            "fd00::1".parse().unwrap(),
+            "fe80::1".parse().unwrap(),
+            "::ffff:127.0.0.1".parse().unwrap(),
+            "::ffff:169.254.169.254".parse().unwrap(),
+        ] {
+            assert!(!public(ip), "{ip} should not be public");
+        }
+
+        for ip in [
+            IpAddr::from([1, 1, 1, 1]),
+            IpAddr::from([100, 128, 0, 1]),
+            "2606:4700:4700::1111".parse().unwrap(),
+        ] {
+            assert!(public(ip), "{ip} should be public");
+        }
+    }
+
+    #[tokio::test]
+    async fn check_host_rejects_internal_urls() {
+        for url in [
+            "http://127.0.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]:8080/",
+            "http://localhost/hook",
+        ] {
+            assert!(
+                check_host(&Url::parse(url).unwrap()).await.is_err(),
+                "{url}"
+            );
+        }
+
+        assert!(
+            check_host(&Url::parse("https://1.1.1.1/hook").unwrap())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn delivers_signed_payload_to_local_stub() {
+        let (addr, mut rx) = stub().await;
+        let payload = r#"{"event":"test","data":{"webhook_id":1}}"#;
+
+        let (status, error) = send(
+            &client(true),
+            true,
+            &format!("http://{addr}"),
+            "secret",
+            "test",
+            42,
+            payload,
+        )
+        .await;
+
+        assert_eq!((status, error), (Some(200), None));
+
+        let (headers, body) = rx.recv().await.unwrap();
+        assert_eq!(body, payload.as_bytes());
+        assert_eq!(headers[EVENT_HEADER], "test");
+        assert_eq!(headers[DELIVERY_HEADER], "42");
+        assert_eq!(headers[SIGNATURE_HEADER], sign("secret", &body).as_str());
+    }
+
+    #[tokio::test]
+    async fn refuses_private_hosts_unless_allowed() {
+        let (addr, mut rx) = stub().await;
+        let port = addr.split_once(':').unwrap().1;
+
+        for url in [format!("http://{addr}"), format!("http://localhost:{port}")] {
+            let (status, error) =
+                send(&client(false), false, &url, "secret", "test", 1, "{}").await;
+
+            assert_eq!(status, None, "{url}");
+            assert!(error.is_some(), "{url}");
+        }
+
+        assert!(rx.try_recv().is_err());
+    }
+}